@@ -0,0 +1,103 @@
+use crate::rc::Rc;
+use std::ops::Range;
+
+/// An immutable byte buffer shared between any number of handles and views.
+#[derive(Clone)]
+pub struct SharedBuffer {
+    bytes: Rc<[u8]>,
+}
+
+impl SharedBuffer {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        SharedBuffer {
+            bytes: Rc::from(bytes),
+        }
+    }
+
+    pub fn byte_slice(&self, range: Range<usize>) -> Option<SharedBytes> {
+        self.bytes.get(range.clone())?;
+        Some(SharedBytes {
+            bytes: self.bytes.clone(),
+            range,
+        })
+    }
+
+    pub fn str_slice(&self, range: Range<usize>) -> Option<SharedStr> {
+        std::str::from_utf8(self.bytes.get(range.clone())?).ok()?;
+        Some(SharedStr {
+            bytes: self.bytes.clone(),
+            range,
+        })
+    }
+}
+
+/// A view into a [`SharedBuffer`] that keeps the underlying bytes alive.
+#[derive(Clone)]
+pub struct SharedBytes {
+    bytes: Rc<[u8]>,
+    range: Range<usize>,
+}
+
+impl std::ops::Deref for SharedBytes {
+    type Target = [u8];
+    fn deref(&self) -> &Self::Target {
+        &self.bytes[self.range.clone()]
+    }
+}
+
+/// A UTF-8 view into a [`SharedBuffer`] that keeps the underlying bytes alive.
+#[derive(Clone)]
+pub struct SharedStr {
+    bytes: Rc<[u8]>,
+    range: Range<usize>,
+}
+
+impl std::ops::Deref for SharedStr {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: str_slice only hands out a SharedStr after checking that this exact range
+        // is in bounds and valid UTF-8, and the buffer is never mutated afterwards.
+        unsafe { std::str::from_utf8_unchecked(&self.bytes[self.range.clone()]) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedBuffer;
+
+    #[test]
+    fn views_outlive_the_buffer_handle() {
+        let buf = SharedBuffer::new(b"hello world".to_vec());
+        let hello = buf.str_slice(0..5).unwrap();
+        let world = buf.byte_slice(6..11).unwrap();
+        drop(buf);
+
+        assert_eq!(&*hello, "hello");
+        assert_eq!(&*world, b"world");
+    }
+
+    #[test]
+    fn cloned_views_share_the_buffer() {
+        let buf = SharedBuffer::new(b"abc".to_vec());
+        let a = buf.str_slice(1..3).unwrap();
+        let b = a.clone();
+        drop(buf);
+        drop(a);
+        assert_eq!(&*b, "bc");
+    }
+
+    #[test]
+    fn out_of_bounds_ranges_are_rejected() {
+        let buf = SharedBuffer::new(b"abc".to_vec());
+        assert!(buf.byte_slice(2..4).is_none());
+        assert!(buf.str_slice(4..5).is_none());
+    }
+
+    #[test]
+    fn str_slice_rejects_invalid_utf8() {
+        let buf = SharedBuffer::new("é".as_bytes().to_vec());
+        assert!(buf.str_slice(0..1).is_none());
+        assert!(buf.byte_slice(0..1).is_some());
+        assert_eq!(&*buf.str_slice(0..2).unwrap(), "é");
+    }
+}
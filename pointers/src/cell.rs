@@ -1,6 +1,9 @@
 use std::cell::UnsafeCell;
 
-pub struct Cell<T> {
+// repr(transparent) guarantees Cell<T> has the same layout as T (via UnsafeCell<T>),
+// which is what lets us reinterpret a Cell<[T]> as a [Cell<T>].
+#[repr(transparent)]
+pub struct Cell<T: ?Sized> {
     value: UnsafeCell<T>,
 }
 
@@ -28,10 +31,101 @@ impl<T> Cell<T> {
         // (because !Sync), and it is executing this function instead.
         unsafe { *self.value.get() }
     }
+
+    pub fn replace(&self, value: T) -> T {
+        // SAFETY: we know no-one else is concurrently accessing self.value (because !Sync)
+        // SAFETY: the &mut we create is the only one, and it is gone before we return: we
+        // never give out references, and no user code runs while we hold it (the old
+        // value is dropped, if at all, by our caller)
+        unsafe { std::mem::replace(&mut *self.value.get(), value) }
+    }
+
+    pub fn swap(&self, other: &Cell<T>) {
+        // Swapping a cell with itself is a no-op, and must not create two &mut to one value.
+        if std::ptr::eq(self, other) {
+            return;
+        }
+        // SAFETY: we know no-one else is concurrently accessing either value (because !Sync)
+        // SAFETY: the two cells are distinct, so the two pointers don't overlap
+        unsafe { std::ptr::swap(self.value.get(), other.value.get()) }
+    }
+
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+
+    /// Replaces the value with `f` applied to it, and returns the new value.
+    pub fn update<F>(&self, f: F) -> T
+    where
+        F: FnOnce(T) -> T,
+        T: Copy,
+    {
+        let new = f(self.get());
+        self.set(new);
+        new
+    }
+}
+
+impl<T: ?Sized> Cell<T> {
+    pub fn from_mut(t: &mut T) -> &Cell<T> {
+        // SAFETY: Cell<T> is transparent over T, and the &mut means no-one else can see the
+        // value for as long as the returned &Cell<T> is around.
+        unsafe { &*(t as *mut T as *const Cell<T>) }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        // &mut self proves no-one else can reach the value, so no interior mutability needed.
+        self.value.get_mut()
+    }
+}
+
+impl<T> Cell<[T]> {
+    pub fn as_slice_of_cells(&self) -> &[Cell<T>] {
+        // SAFETY: Cell<[T]> and [Cell<T>] have the same layout (both transparent over
+        // [T]), and the slice of cells is just as !Sync as the cell it came from.
+        unsafe { &*(self as *const Cell<[T]> as *const [Cell<T>]) }
+    }
+}
+
+impl<T: Default> Default for Cell<T> {
+    fn default() -> Self {
+        Cell::new(T::default())
+    }
+}
+
+impl<T> From<T> for Cell<T> {
+    fn from(value: T) -> Self {
+        Cell::new(value)
+    }
+}
+
+// Clone, PartialEq and Debug all read the value through get, since handing out a reference
+// to the inside is exactly what a Cell must never do. That's why they require T: Copy.
+impl<T: Copy> Clone for Cell<T> {
+    fn clone(&self) -> Self {
+        Cell::new(self.get())
+    }
+}
+
+impl<T: Copy + PartialEq> PartialEq for Cell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Copy + std::fmt::Debug> std::fmt::Debug for Cell<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cell").field("value", &self.get()).finish()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Cell;
+
     // use super::*;
     //
     // // these aren't _quite_ functional tests,
@@ -103,4 +197,145 @@ mod tests {
     //     jh2.join().unwrap();
     //     assert_eq!(x.get(), 2000000);
     // }
+
+    #[test]
+    fn update_increments() {
+        let c = Cell::new(41usize);
+        assert_eq!(c.update(|x| x + 1), 42);
+        assert_eq!(c.get(), 42);
+    }
+
+    #[test]
+    fn update_composes_when_repeated() {
+        let c = Cell::new(1u64);
+        for _ in 0..10 {
+            c.update(|x| x * 2);
+        }
+        c.update(|x| x - 24);
+        assert_eq!(c.get(), 1000);
+    }
+
+    #[test]
+    fn take_leaves_none_behind() {
+        let slot = Cell::new(Some(String::from("once")));
+        assert_eq!(slot.take(), Some(String::from("once")));
+        assert_eq!(slot.take(), None);
+    }
+
+    #[test]
+    fn take_leaves_empty_vec_behind() {
+        let buf = Cell::new(vec![1u8, 2, 3]);
+        assert_eq!(buf.take(), [1, 2, 3]);
+        assert!(buf.take().is_empty());
+    }
+
+    #[test]
+    fn replace_returns_old_value() {
+        let c = Cell::new(String::from("old"));
+        let old = c.replace(String::from("new"));
+        assert_eq!(old, "old");
+        assert_eq!(c.take(), "new");
+    }
+
+    #[test]
+    fn swap_exchanges_values() {
+        let a = Cell::new(String::from("a"));
+        let b = Cell::new(String::from("b"));
+        a.swap(&b);
+        assert_eq!(a.take(), "b");
+        assert_eq!(b.take(), "a");
+    }
+
+    #[test]
+    fn swap_with_itself_is_a_no_op() {
+        let a = Cell::new(String::from("a"));
+        a.swap(&a);
+        assert_eq!(a.take(), "a");
+    }
+
+    #[test]
+    fn swap_neither_clones_nor_drops() {
+        use std::rc::Rc;
+
+        /// Counts drops in a shared cell, and refuses to be cloned.
+        struct DropSpy(Rc<Cell<usize>>);
+
+        impl Drop for DropSpy {
+            fn drop(&mut self) {
+                self.0.update(|n| n + 1);
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let a = Cell::new(DropSpy(drops.clone()));
+        let b = Cell::new(DropSpy(drops.clone()));
+        a.swap(&b);
+        a.swap(&a);
+        assert_eq!(drops.get(), 0);
+
+        drop(a);
+        drop(b);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn as_slice_of_cells_sets_single_element() {
+        let array = Cell::new([1, 2, 3, 4]);
+        let slice: &Cell<[i32]> = &array;
+        let cells = slice.as_slice_of_cells();
+        assert_eq!(cells.len(), 4);
+
+        cells[2].set(30);
+        assert_eq!(array.get(), [1, 2, 30, 4]);
+    }
+
+    #[test]
+    fn get_mut_mutates_in_place() {
+        let mut c = Cell::new(5);
+        *c.get_mut() += 1;
+        assert_eq!(c.get(), 6);
+    }
+
+    #[test]
+    fn derive_default_with_cell_field() {
+        #[derive(Default, Debug, PartialEq)]
+        struct Counter {
+            hits: Cell<u32>,
+        }
+
+        let a = Counter::default();
+        assert_eq!(a.hits.get(), 0);
+        a.hits.set(3);
+
+        let b = Counter { hits: a.hits.clone() };
+        assert_eq!(a, b);
+        b.hits.set(4);
+        assert_ne!(a, b);
+        assert_eq!(format!("{:?}", a), "Counter { hits: Cell { value: 3 } }");
+    }
+
+    #[test]
+    fn from_value() {
+        let n: Cell<i32> = 5.into();
+        assert_eq!(n.get(), 5);
+
+        let s: Cell<String> = String::from("into").into();
+        assert_eq!(s.take(), "into");
+    }
+
+    #[test]
+    fn from_mut_writes_through() {
+        let mut n = 1;
+        {
+            let c = Cell::from_mut(&mut n);
+            c.set(c.get() + 1);
+            c.update(|v| v * 10);
+        }
+        assert_eq!(n, 20);
+
+        let mut v = [1, 2, 3];
+        let cells = Cell::from_mut(&mut v[..]).as_slice_of_cells();
+        cells[0].swap(&cells[2]);
+        assert_eq!(v, [3, 2, 1]);
+    }
 }
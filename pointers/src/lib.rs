@@ -1,3 +1,7 @@
+#![cfg_attr(feature = "nightly", feature(coerce_unsized, unsize))]
+
+pub mod buffer;
 pub mod cell;
 pub mod rc;
 pub mod refcell;
+pub mod sync;
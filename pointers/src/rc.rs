@@ -1,17 +1,59 @@
 use crate::cell::Cell;
+use std::alloc::{self, Layout};
+use std::any::Any;
 use std::marker::PhantomData;
-use std::ptr::NonNull;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::pin::Pin;
+use std::ptr::{self, NonNull};
 
-struct RcInner<T> {
-    value: T,
+// Layout: the two counts first, then the value. #[repr(C)] pins that order, which lets us
+// build an RcInner<[T]> by hand: a two-usize header, padding up to T's alignment, and then
+// the elements inline. The slice length lives in the fat pointer, not in the allocation.
+// value has to come last regardless, so that RcInner<T> may be unsized along with T.
+// Fixing the layout also makes Rc::allocation_size predictable: two usizes, then T, padded
+// out to the larger of the two alignments.
+//
+// Like std, all the strong references together hold one extra `weak` count, which keeps
+// the allocation alive until both the value has been dropped and the last Weak is gone.
+#[repr(C)]
+struct RcInner<T: ?Sized> {
     refcount: Cell<usize>,
+    weak: Cell<usize>,
+    value: T,
+}
+
+#[derive(Debug, PartialEq)]
+struct RefcountOverflow;
+
+/// Bumps `count` by one, leaving it untouched if that would overflow.
+fn increment_checked(count: &Cell<usize>) -> Result<(), RefcountOverflow> {
+    let c = count.get().checked_add(1).ok_or(RefcountOverflow)?;
+    count.set(c);
+    Ok(())
+}
+
+fn increment(count: &Cell<usize>) {
+    if increment_checked(count).is_err() {
+        // A wrapped count would free the value while owners are still around. Like std,
+        // abort rather than panic: unwinding could run code that still uses the Rc.
+        std::process::abort();
+    }
 }
 
-pub struct Rc<T> {
+pub struct Rc<T: ?Sized> {
     inner: NonNull<RcInner<T>>,
     _marker: PhantomData<RcInner<T>>,
 }
 
+pub struct Weak<T: ?Sized> {
+    inner: NonNull<RcInner<T>>,
+    // Layout of the whole allocation, worked out while value was still alive. By the time
+    // the last Weak frees the memory, value has been dropped (or, if new_cyclic's data_fn
+    // panicked, was never written), so Layout::for_value can't be used on it anymore.
+    layout: Layout,
+    _marker: PhantomData<RcInner<T>>,
+}
+
 impl<T> Rc<T> {
     pub fn new(v: T) -> Self {
         //     let inner = Box::new(RcInner {
@@ -27,6 +69,7 @@ impl<T> Rc<T> {
         let boxed = Box::new(RcInner {
             value: v,
             refcount: Cell::new(1),
+            weak: Cell::new(1),
         });
 
         let inner = NonNull::from(Box::leak(boxed));
@@ -37,20 +80,429 @@ impl<T> Rc<T> {
         }
     }
 
+    /// Pins `value` behind shared ownership.
+    ///
+    /// This is sound because an `Rc` never moves its value, and a `Pin<Rc<T>>` gives no
+    /// safe way back to the `Rc` (and so to `get_mut`, `make_mut` or `try_unwrap`) unless
+    /// `T: Unpin`. Code that unsafely unwraps the pin must not use those APIs either.
+    pub fn pin(value: T) -> Pin<Rc<T>> {
+        // SAFETY: see above; the value stays put until the last Rc drops it in place.
+        unsafe { Pin::new_unchecked(Rc::new(value)) }
+    }
+
+    /// Allocates room for a `T` without initializing it, so large values can be written
+    /// in place (through [`Rc::get_mut`]) instead of being built on the stack first.
+    pub fn new_uninit() -> Rc<MaybeUninit<T>> {
+        let mut boxed = Box::<RcInner<MaybeUninit<T>>>::new_uninit();
+        let ptr = boxed.as_mut_ptr();
+        // SAFETY: ptr points at the freshly allocated RcInner; value is MaybeUninit, so
+        // only the counts need writing for the whole thing to be initialized.
+        let boxed = unsafe {
+            ptr::addr_of_mut!((*ptr).refcount).write(Cell::new(1));
+            ptr::addr_of_mut!((*ptr).weak).write(Cell::new(1));
+            boxed.assume_init()
+        };
+        Rc {
+            inner: NonNull::from(Box::leak(boxed)),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Rc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        // We take over the last reference ourselves, so this Rc's Drop must not run.
+        let this = ManuallyDrop::new(this);
+        let inner = this.inner();
+        let layout = Layout::for_value(inner);
+        inner.refcount.set(0);
+        // SAFETY: we were the only Rc, and with the count at 0 no Weak can upgrade and
+        // observe the value again, so moving it out can't lead to a double drop.
+        let value = unsafe { ptr::read(&inner.value) };
+        // Release the weak count held by the strong references; frees the allocation
+        // unless some Weak is still holding on to it.
+        drop(Weak {
+            inner: this.inner,
+            layout,
+            _marker: PhantomData,
+        });
+        Ok(value)
+    }
+
+    pub fn new_cyclic<F>(data_fn: F) -> Self
+    where
+        F: FnOnce(&Weak<T>) -> T,
+    {
+        // Start with no strong references, so the Weak can't be upgraded while T is
+        // still being built, and a weak count of 1 for the Weak we hand to data_fn.
+        let uninit = Box::new(RcInner {
+            refcount: Cell::new(0),
+            weak: Cell::new(1),
+            value: MaybeUninit::<T>::uninit(),
+        });
+        // RcInner is #[repr(C)] and MaybeUninit<T> has the layout of T, so this cast
+        // is just a change of view.
+        let inner = NonNull::from(Box::leak(uninit)).cast::<RcInner<T>>();
+        let weak = Weak {
+            inner,
+            layout: Layout::new::<RcInner<T>>(),
+            _marker: PhantomData,
+        };
+
+        let value = data_fn(&weak);
+
+        // SAFETY: the allocation is live since weak holds on to it, and nobody can have
+        // read value since the strong count is still 0.
+        unsafe {
+            ptr::addr_of_mut!((*inner.as_ptr()).value).write(value);
+        }
+        weak.refcount().set(1);
+        // The weak count we started with becomes the one held by the strong references.
+        std::mem::forget(weak);
+
+        Rc {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+    {
+        if Rc::strong_count(this) != 1 || this.inner().weak.get() != 1 {
+            // Someone else can see (or upgrade to) the value, so give ourselves a private
+            // copy. The assignment drops our share of the old allocation.
+            *this = Rc::new((**this).clone());
+        }
+        Rc::get_mut(this).expect("Rc is unique after make_mut cloned it")
+    }
+
+    /// Like [`Rc::make_mut`], but hands back the unique `Rc` itself.
+    pub fn make_unique(mut this: Self) -> Self
+    where
+        T: Clone,
+    {
+        Rc::make_mut(&mut this);
+        this
+    }
+}
+
+impl<T: ?Sized> Rc<T> {
+    fn inner(&self) -> &RcInner<T> {
+        // SAFETY: inner points to a valid RcInner while any Rc to it is alive.
+        unsafe { self.inner.as_ref() }
+    }
+
     pub fn get_mut(this: &mut Self) -> Option<&mut T> {
-        // SAFETY: we have &mut self; if refcount==1, no other Rc exists, so &mut T is fine.
+        // SAFETY: we have &mut self; if refcount==1, no other Rc exists, and if weak==1,
+        // no Weak exists that could upgrade into one, so &mut T is fine.
         unsafe {
             let ptr = this.inner.as_ptr();
-            if (*ptr).refcount.get() == 1 {
+            if (*ptr).refcount.get() == 1 && (*ptr).weak.get() == 1 {
                 Some(&mut (*ptr).value)
             } else {
                 None
             }
         }
     }
+
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        increment(&this.inner().weak);
+        Weak {
+            inner: this.inner,
+            layout: Layout::for_value(this.inner()),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        // Compare addresses only: vtables for the same type aren't guaranteed to be unique.
+        std::ptr::addr_eq(a.inner.as_ptr(), b.inner.as_ptr())
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().refcount.get()
+    }
+
+    /// Bytes taken up by the shared allocation: the value, both counts, and any padding.
+    pub fn allocation_size(this: &Self) -> usize {
+        std::mem::size_of_val(this.inner())
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        // Leave out the implicit weak the strong references share.
+        this.inner().weak.get() - 1
+    }
+
+    pub fn as_ptr(this: &Self) -> *const T {
+        // SAFETY: inner is valid; addr_of! projects to value without creating a reference.
+        unsafe { ptr::addr_of!((*this.inner.as_ptr()).value) }
+    }
+
+    /// Leaks `this`, returning a pointer to the value that can be turned back into an
+    /// `Rc` with [`Rc::from_raw`]. The strong count is left untouched.
+    pub fn into_raw(this: Self) -> *const T {
+        let ptr = Rc::as_ptr(&this);
+        std::mem::forget(this);
+        ptr
+    }
+
+    /// Reconstitutes an `Rc` from a pointer returned by [`Rc::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have come from `Rc::<T>::into_raw`, and each such pointer may only be
+    /// turned back into an `Rc` once, since `into_raw` only leaked a single count.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        // The value sits right after the counts, padded up to its own alignment.
+        // SAFETY: ptr points at a live value, per the caller's contract.
+        let align = std::mem::align_of_val(unsafe { &*ptr });
+        let (_, offset) = Layout::new::<RcInner<()>>()
+            .extend(Layout::from_size_align(0, align).expect("align_of_val is a valid alignment"))
+            .expect("zero-sized extension can't overflow");
+        // SAFETY: ptr lies offset bytes into the RcInner that into_raw got it from;
+        // byte_sub keeps any slice length or vtable in the pointer metadata.
+        let inner = unsafe { ptr.byte_sub(offset) } as *mut RcInner<T>;
+        Rc {
+            // SAFETY: derived from a non-null allocation.
+            inner: unsafe { NonNull::new_unchecked(inner) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Weak<T> {
+    // These only ever borrow the counts, never all of RcInner: value may already be gone,
+    // or be in the middle of being dropped (when the Weak lives inside T itself).
+    fn refcount(&self) -> &Cell<usize> {
+        // SAFETY: the allocation, and with it the counts, stays around while any Weak is alive.
+        unsafe { &*ptr::addr_of!((*self.inner.as_ptr()).refcount) }
+    }
+
+    fn weak(&self) -> &Cell<usize> {
+        // SAFETY: as for refcount.
+        unsafe { &*ptr::addr_of!((*self.inner.as_ptr()).weak) }
+    }
+
+    pub fn upgrade(&self) -> Option<Rc<T>> {
+        let c = self.refcount().get();
+        if c == 0 {
+            return None;
+        }
+        increment(self.refcount());
+        Some(Rc {
+            inner: self.inner,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        increment(self.weak());
+        Weak {
+            inner: self.inner,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        if self.weak().update(|w| w - 1) == 0 {
+            // SAFETY: no Rc or Weak is left, and value has either been dropped already or
+            // was never initialized, so all that is left to do is free the memory.
+            unsafe { alloc::dealloc(self.inner.as_ptr().cast(), self.layout) };
+        }
+    }
+}
+
+impl Rc<dyn Any> {
+    pub fn downcast<U: Any>(self) -> Result<Rc<U>, Rc<dyn Any>> {
+        if !(*self).is::<U>() {
+            return Err(self);
+        }
+        let this = ManuallyDrop::new(self);
+        Ok(Rc {
+            // The value really is a U, so drop the vtable and keep the same allocation
+            // and count.
+            inner: this.inner.cast(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T> Rc<MaybeUninit<T>> {
+    /// Converts to `Rc<T>`, keeping the same allocation and counts.
+    ///
+    /// # Safety
+    ///
+    /// The value must have been fully initialized, typically through [`Rc::get_mut`] on
+    /// the `Rc` returned by [`Rc::new_uninit`].
+    pub unsafe fn assume_init(this: Self) -> Rc<T> {
+        let this = ManuallyDrop::new(this);
+        Rc {
+            // RcInner is #[repr(C)] and MaybeUninit<T> has the layout of T, so
+            // RcInner<MaybeUninit<T>> and RcInner<T> are laid out identically.
+            inner: this.inner.cast(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Rc<[T]> {
+    fn slice_layout(len: usize) -> Layout {
+        let (layout, _) = Layout::new::<RcInner<()>>()
+            .extend(Layout::array::<T>(len).expect("Rc<[T]> capacity overflow"))
+            .expect("Rc<[T]> capacity overflow");
+        layout.pad_to_align()
+    }
+
+    /// Builds an Rc<[T]> out of exactly `len` elements from `iter`, in a single allocation.
+    ///
+    /// Panics (without leaking or double-dropping) if `iter` yields more or fewer elements.
+    fn from_iter_exact(mut iter: impl Iterator<Item = T>, len: usize) -> Self {
+        // Frees the allocation, and drops whatever was written so far, if we unwind.
+        struct Guard<T> {
+            mem: *mut u8,
+            layout: Layout,
+            elems: *mut T,
+            written: usize,
+        }
+
+        impl<T> Drop for Guard<T> {
+            fn drop(&mut self) {
+                // SAFETY: the first `written` elements were initialized, and mem was
+                // allocated with layout.
+                unsafe {
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.elems, self.written));
+                    alloc::dealloc(self.mem, self.layout);
+                }
+            }
+        }
+
+        let layout = Self::slice_layout(len);
+        // SAFETY: layout is never zero-sized, since it always has room for the counts.
+        let mem = unsafe { alloc::alloc(layout) };
+        if mem.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        // The cast keeps the slice length as the metadata of the new fat pointer.
+        let inner = ptr::slice_from_raw_parts_mut(mem.cast::<T>(), len) as *mut RcInner<[T]>;
+
+        // SAFETY: inner points into an allocation sized and aligned for RcInner<[T]>
+        // with len elements, so the field projections stay in bounds.
+        unsafe {
+            let elems = ptr::addr_of_mut!((*inner).value).cast::<T>();
+            let mut guard = Guard {
+                mem,
+                layout,
+                elems,
+                written: 0,
+            };
+            for elem in iter.by_ref().take(len) {
+                elems.add(guard.written).write(elem);
+                guard.written += 1;
+            }
+            assert_eq!(guard.written, len, "iterator yielded fewer elements than promised");
+            assert!(iter.next().is_none(), "iterator yielded more elements than promised");
+            std::mem::forget(guard);
+
+            ptr::addr_of_mut!((*inner).refcount).write(Cell::new(1));
+            ptr::addr_of_mut!((*inner).weak).write(Cell::new(1));
+            Rc {
+                inner: NonNull::new_unchecked(inner),
+                _marker: PhantomData,
+            }
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Rc<[T]> {
+    fn from(v: &[T]) -> Self {
+        Rc::from_iter_exact(v.iter().cloned(), v.len())
+    }
+}
+
+impl<T> From<Vec<T>> for Rc<[T]> {
+    fn from(v: Vec<T>) -> Self {
+        let len = v.len();
+        Rc::from_iter_exact(v.into_iter(), len)
+    }
+}
+
+/// Collecting into an `Rc<[T]>` allocates exactly once when the iterator knows its length
+/// up front (its `size_hint` is exact, as for any `ExactSizeIterator`): the elements are
+/// moved straight into the shared allocation. Otherwise they are first collected into a
+/// temporary `Vec`, and then moved over into a right-sized allocation.
+///
+/// An iterator whose exact `size_hint` turns out to be wrong causes a panic.
+impl<T> FromIterator<T> for Rc<[T]> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Rc::from_iter_exact(iter, lower),
+            _ => Rc::from(iter.collect::<Vec<T>>()),
+        }
+    }
+}
+
+impl From<String> for Rc<str> {
+    fn from(s: String) -> Self {
+        let bytes = ManuallyDrop::new(Rc::<[u8]>::from(s.into_bytes()));
+        Rc {
+            // SAFETY: str and [u8] share their layout and metadata, and the bytes came from a
+            // String, so they're valid UTF-8.
+            inner: unsafe { NonNull::new_unchecked(bytes.inner.as_ptr() as *mut RcInner<str>) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<T> for Rc<T> {
+    fn from(value: T) -> Self {
+        Rc::new(value)
+    }
 }
 
-impl<T> std::ops::Deref for Rc<T> {
+impl<T> From<Box<T>> for Rc<T> {
+    fn from(b: Box<T>) -> Self {
+        let mut rc = Rc::<T>::new_uninit();
+        let slot = Rc::get_mut(&mut rc).expect("fresh Rc is unique");
+        let raw = Box::into_raw(b);
+        // SAFETY: raw is a valid, uniquely owned T, and slot has room for one. Copying the
+        // bytes straight across means a large T never passes through the stack.
+        unsafe {
+            ptr::copy_nonoverlapping(raw, slot.as_mut_ptr(), 1);
+            // Free the Box's memory without dropping the value that now lives in rc.
+            drop(Box::from_raw(raw.cast::<ManuallyDrop<T>>()));
+            Rc::assume_init(rc)
+        }
+    }
+}
+
+pub struct RcFn;
+
+impl RcFn {
+    /// Shares `f` as an `Rc<dyn Fn(E)>`, so several registries can hold the same callback.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<E, F>(f: F) -> Rc<dyn Fn(E)>
+    where
+        F: Fn(E) + 'static,
+    {
+        let rc = ManuallyDrop::new(Rc::new(f));
+        Rc {
+            // NonNull<RcInner<F>> unsizes to NonNull<RcInner<dyn Fn(E)>>; the count moves over as-is.
+            inner: rc.inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> std::ops::Deref for Rc<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         //// SAFETY: self.inner is a Box that is only deallocated when the last Rc goes away.
@@ -62,22 +514,46 @@ impl<T> std::ops::Deref for Rc<T> {
     }
 }
 
-impl<T> Clone for Rc<T> {
+// Unlike std's AsRef<T>, this forwards whatever T itself converts to, so an Rc<String>
+// works as AsRef<str> and an Rc<Vec<u8>> as AsRef<[u8]>.
+impl<T, U> AsRef<U> for Rc<T>
+where
+    T: ?Sized + AsRef<U>,
+    U: ?Sized,
+{
+    fn as_ref(&self) -> &U {
+        (**self).as_ref()
+    }
+}
+
+impl<T: ?Sized> std::borrow::Borrow<T> for Rc<T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> Clone for Rc<T> {
     fn clone(&self) -> Self {
         // SAFETY: reading and writing the Cell is fine (single-threaded).
         let inner = unsafe { self.inner.as_ref() };
-        let c = inner.refcount.get();
-        debug_assert!(c != usize::MAX, "Rc refcount overflow");
-        inner.refcount.set(c + 1);
+        increment(&inner.refcount);
         Rc {
             inner: self.inner,
             _marker: PhantomData,
         }
     }
+
+    fn clone_from(&mut self, source: &Self) {
+        // Already sharing source's allocation: the counts are right as they are.
+        if !Rc::ptr_eq(self, source) {
+            // Bumps source's count, then drops ours (freeing it if we were the last).
+            *self = source.clone();
+        }
+    }
 }
 
 // TODO: #[may_dangle] (advanced; lets Drop run even if T's destructor could observe a partially dropped value)
-impl<T> Drop for Rc<T> {
+impl<T: ?Sized> Drop for Rc<T> {
     // fn drop(&mut self) {
     //     let inner = unsafe { self.inner.as_ref() };
     //     let c = inner.refcount.get();
@@ -94,28 +570,86 @@ impl<T> Drop for Rc<T> {
     fn drop(&mut self) {
         unsafe {
             let ptr = self.inner.as_ptr();
-            // Read the count without keeping an & alive across the free.
-            let c = (*ptr).refcount.get();
-            if c == 1 {
-                // Drop the allocation (drops T then frees the box).
-                drop(Box::from_raw(ptr));
-            } else {
-                (*ptr).refcount.set(c - 1);
+            // Update the count without keeping an & alive across the free.
+            if (*ptr).refcount.update(|c| c - 1) == 0 {
+                // Last chance to look at the value's size, before it's gone.
+                let layout = Layout::for_value(&*ptr);
+                // Drop T in place; the memory itself belongs to the weak count now.
+                ptr::drop_in_place(ptr::addr_of_mut!((*ptr).value));
+                // Release the weak count held by the strong references, which frees the
+                // allocation if there are no Weaks left.
+                drop(Weak {
+                    inner: self.inner,
+                    layout,
+                    _marker: PhantomData,
+                });
             }
         }
     }
 }
 
+#[cfg(feature = "nightly")]
+impl<T, U> std::ops::CoerceUnsized<Rc<U>> for Rc<T>
+where
+    T: ?Sized + std::marker::Unsize<U>,
+    U: ?Sized,
+{
+}
+
+// Two Rcs to the same allocation are equal without looking at the value. That's only a
+// shortcut if T's equality is reflexive: a shared Rc<f64> holding NaN compares equal to
+// itself here, even though NaN != NaN.
+impl<T: ?Sized + PartialEq> PartialEq for Rc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(self, other) || **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Rc<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for Rc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+// Only as ordered as T: Rc<f64> is PartialOrd but not Ord, since f64 isn't (NaN), so it
+// can't be a BTreeMap key. Wrap the float in a totally ordered type first.
+impl<T: ?Sized + Ord> Ord for Rc<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + std::hash::Hash> std::hash::Hash for Rc<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: ?Sized + std::fmt::Display> std::fmt::Display for Rc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for Rc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Rc;
+    use super::{Rc, RcFn, RefcountOverflow, Weak, increment, increment_checked};
+    use crate::cell::Cell;
     use std::sync::{
         Arc,
         atomic::{AtomicUsize, Ordering},
     };
 
     /// A value that bumps a shared counter when dropped.
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     struct DropSpy {
         drops: Arc<AtomicUsize>,
         _id: &'static str,
@@ -174,7 +708,7 @@ mod tests {
         assert_eq!(drops.load(Ordering::SeqCst), 0);
 
         // Drop all the clones; the inner should drop exactly once at the very end.
-        while let Some(_) = v.pop() {}
+        while v.pop().is_some() {}
         assert_eq!(drops.load(Ordering::SeqCst), 1);
     }
 
@@ -204,4 +738,816 @@ mod tests {
         let mut r2 = r.clone();
         assert!(Rc::get_mut(&mut r2).is_none());
     }
+
+    #[test]
+    fn allocation_size_counts_header_and_value() {
+        use std::mem::size_of;
+
+        let a = Rc::new(1u8);
+        let size = Rc::allocation_size(&a);
+        assert!(size >= size_of::<u8>() + size_of::<usize>());
+        // two counts, then the u8, padded back out to usize alignment
+        assert_eq!(size, 3 * size_of::<usize>());
+        let b = a.clone();
+        assert_eq!(Rc::allocation_size(&b), size);
+
+        let s = Rc::new([0u64; 4]);
+        assert_eq!(Rc::allocation_size(&s), 2 * size_of::<usize>() + 32);
+
+        let slice: Rc<[u16]> = Rc::from(vec![1, 2, 3]);
+        let size = Rc::allocation_size(&slice);
+        assert!(size >= 2 * size_of::<usize>() + 3 * size_of::<u16>());
+        assert_eq!(size % std::mem::align_of::<usize>(), 0);
+    }
+
+    #[test]
+    fn weak_count_tracks_downgrades() {
+        let r = Rc::new(1);
+        assert_eq!(Rc::weak_count(&r), 0);
+        let w1 = Rc::downgrade(&r);
+        let w2 = Rc::downgrade(&r);
+        assert_eq!(Rc::weak_count(&r), 2);
+        drop(w1);
+        assert_eq!(Rc::weak_count(&r), 1);
+        let r2 = w2.upgrade().expect("still alive");
+        assert_eq!(Rc::strong_count(&r), 2);
+        assert_eq!(Rc::weak_count(&r), 1);
+        drop(r2);
+        drop(w2);
+        assert_eq!(Rc::weak_count(&r), 0);
+        assert_eq!(Rc::strong_count(&r), 1);
+    }
+
+    #[test]
+    fn get_mut_refused_while_weak_outstanding() {
+        let mut r = Rc::new(10);
+        let w = Rc::downgrade(&r);
+        assert_eq!(Rc::strong_count(&r), 1);
+        // Only one strong Rc, but the Weak could still upgrade into a second one.
+        assert!(Rc::get_mut(&mut r).is_none());
+
+        drop(w);
+        *Rc::get_mut(&mut r).expect("no Weak left") += 1;
+        assert_eq!(*r, 11);
+    }
+
+    #[test]
+    fn strong_count_tracks_clones_and_drops() {
+        let a = Rc::new(1);
+        assert_eq!(Rc::strong_count(&a), 1);
+
+        let b = a.clone();
+        let c = b.clone();
+        assert_eq!(Rc::strong_count(&a), 3);
+        assert_eq!(Rc::strong_count(&c), 3);
+
+        drop(b);
+        assert_eq!(Rc::strong_count(&a), 2);
+
+        drop(c);
+        assert_eq!(Rc::strong_count(&a), 1);
+    }
+
+    #[test]
+    fn try_unwrap_moves_value_out_when_unique() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let r = Rc::new(DropSpy { drops: drops.clone(), _id: "unwrap" });
+
+        let Ok(spy) = Rc::try_unwrap(r) else {
+            panic!("unique Rc must unwrap");
+        };
+        // The value was moved out, not dropped.
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        drop(spy);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn try_unwrap_fails_when_shared() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let a = Rc::new(DropSpy { drops: drops.clone(), _id: "shared" });
+        let b = a.clone();
+
+        let Err(a) = Rc::try_unwrap(a) else {
+            panic!("shared Rc must not unwrap");
+        };
+        assert_eq!(Rc::strong_count(&a), 2);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        drop(b);
+        let Ok(spy) = Rc::try_unwrap(a) else {
+            panic!("expected unique access");
+        };
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(spy);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn ptr_eq_compares_identity_not_value() {
+        let a = Rc::new(7);
+        let b = a.clone();
+        let c = Rc::new(7);
+
+        assert!(Rc::ptr_eq(&a, &b));
+        assert!(!Rc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    fn ptr_eq_with_itself() {
+        let a = Rc::new(String::from("self"));
+        assert!(Rc::ptr_eq(&a, &a));
+    }
+
+    #[test]
+    fn rc_fn_shared_between_registries_drops_once() {
+        struct Event(usize);
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let spy = DropSpy { drops: drops.clone(), _id: "callback" };
+        let seen = calls.clone();
+        let callback = RcFn::new(move |event: Event| {
+            let _ = &spy;
+            seen.fetch_add(event.0, Ordering::SeqCst);
+        });
+
+        let mut first: Vec<Rc<dyn Fn(Event)>> = vec![callback.clone()];
+        let mut second: Vec<Rc<dyn Fn(Event)>> = vec![callback];
+        assert!(Rc::ptr_eq(&first[0], &second[0]));
+
+        for cb in first.iter().chain(second.iter()) {
+            cb(Event(1));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        first.clear();
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        second.clear();
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn eq_and_hash_forward_to_value() {
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+        assert!(set.insert(Rc::new(String::from("a"))));
+        assert!(set.insert(Rc::new(String::from("b"))));
+        // A separate allocation with an equal value is a duplicate.
+        assert!(!set.insert(Rc::new(String::from("a"))));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Rc::new(String::from("b"))));
+    }
+
+    #[test]
+    fn ord_forwards_to_value() {
+        let mut v: Vec<Rc<i32>> = [3, 1, 2].into_iter().map(Rc::new).collect();
+        v.sort();
+        let values: Vec<i32> = v.iter().map(|r| **r).collect();
+        assert_eq!(values, [1, 2, 3]);
+        assert!(Rc::new(1.0) < Rc::new(2.0));
+    }
+
+    #[test]
+    fn btreemap_keys_sort_by_value() {
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        for (i, key) in ["pear", "apple", "fig", "banana"].into_iter().enumerate() {
+            map.insert(Rc::new(String::from(key)), i);
+        }
+        // An equal value in a separate allocation finds the same entry.
+        assert_eq!(map.get(&Rc::new(String::from("fig"))), Some(&2));
+
+        let keys: Vec<&str> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, ["apple", "banana", "fig", "pear"]);
+    }
+
+    #[test]
+    fn fmt_forwards_to_value() {
+        let n = Rc::new(42);
+        assert_eq!(format!("{}", n), "42");
+        assert_eq!(format!("{:?}", n), "42");
+        assert_eq!(format!("{:>4}", n), "  42");
+
+        let s = Rc::new(String::from("shared"));
+        assert_eq!(format!("{}", s), "shared");
+        assert_eq!(format!("{:?}", s), "\"shared\"");
+
+        let slice: Rc<[i32]> = Rc::from(vec![1, 2]);
+        assert_eq!(format!("{:?}", slice), "[1, 2]");
+    }
+
+    #[test]
+    fn eq_skips_value_for_shared_allocation() {
+        struct Counted<'a> {
+            value: String,
+            compares: &'a Cell<usize>,
+        }
+
+        impl PartialEq for Counted<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.compares.set(self.compares.get() + 1);
+                self.value == other.value
+            }
+        }
+
+        let compares = Cell::new(0);
+        let a = Rc::new(Counted {
+            value: "x".repeat(1024),
+            compares: &compares,
+        });
+        let b = a.clone();
+        assert!(a == b);
+        assert_eq!(compares.get(), 0);
+
+        let c = Rc::new(Counted {
+            value: "x".repeat(1024),
+            compares: &compares,
+        });
+        assert!(a == c);
+        assert_eq!(compares.get(), 1);
+
+        // The documented subtlety: NaN is equal to itself once shared.
+        let nan = Rc::new(f64::NAN);
+        assert!(nan == nan.clone());
+        assert!(nan != Rc::new(f64::NAN));
+    }
+
+    #[test]
+    fn slice_from_ref_clones_elements() {
+        let a: Rc<[i32]> = Rc::from(&[1, 2, 3][..]);
+        let b = a.clone();
+
+        assert_eq!(a.len(), 3);
+        assert_eq!(b[1], 2);
+        assert_eq!(&*a, &[1, 2, 3]);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(Rc::strong_count(&a), 2);
+    }
+
+    #[test]
+    fn empty_slice() {
+        let a: Rc<[String]> = Rc::from(&[][..]);
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn slice_elements_drop_exactly_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let spies = [
+            DropSpy { drops: drops.clone(), _id: "first" },
+            DropSpy { drops: drops.clone(), _id: "second" },
+            DropSpy { drops: drops.clone(), _id: "third" },
+        ];
+
+        let a: Rc<[DropSpy]> = Rc::from(&spies[..]);
+        let b = a.clone();
+        drop(a);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        drop(b);
+        assert_eq!(drops.load(Ordering::SeqCst), 3);
+
+        drop(spies);
+        assert_eq!(drops.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn slice_clone_panic_does_not_leak_or_double_drop() {
+        struct Bomb<'a> {
+            drops: &'a AtomicUsize,
+            explode: bool,
+        }
+
+        impl Clone for Bomb<'_> {
+            fn clone(&self) -> Self {
+                assert!(!self.explode, "boom");
+                Bomb { drops: self.drops, explode: false }
+            }
+        }
+
+        impl Drop for Bomb<'_> {
+            fn drop(&mut self) {
+                self.drops.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = AtomicUsize::new(0);
+        let bombs = [
+            Bomb { drops: &drops, explode: false },
+            Bomb { drops: &drops, explode: false },
+            Bomb { drops: &drops, explode: true },
+        ];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _: Rc<[Bomb<'_>]> = Rc::from(&bombs[..]);
+        }));
+        assert!(result.is_err());
+        // Only the two clones that were completed got dropped.
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn make_mut_mutates_in_place_when_unique() {
+        let mut a = Rc::new(String::from("hello"));
+        let before: *const String = &*a;
+        Rc::make_mut(&mut a).push_str(" world");
+
+        assert_eq!(*a, "hello world");
+        assert_eq!(before, &*a as *const String);
+    }
+
+    #[test]
+    fn make_mut_clones_when_shared() {
+        let mut a = Rc::new(String::from("hello"));
+        let b = a.clone();
+        Rc::make_mut(&mut a).push_str(" world");
+
+        assert_eq!(*a, "hello world");
+        assert_eq!(*b, "hello");
+        assert!(!Rc::ptr_eq(&a, &b));
+        assert_eq!(Rc::strong_count(&a), 1);
+        assert_eq!(Rc::strong_count(&b), 1);
+    }
+
+    #[test]
+    fn make_unique_keeps_unique_allocation() {
+        let a = Rc::new(String::from("solo"));
+        let before = Rc::as_ptr(&a);
+        let a = Rc::make_unique(a);
+        assert_eq!(Rc::as_ptr(&a), before);
+        assert_eq!(*a, "solo");
+    }
+
+    #[test]
+    fn make_unique_clones_shared_allocation() {
+        let a = Rc::new(String::from("shared"));
+        let b = Rc::make_unique(a.clone());
+        assert!(!Rc::ptr_eq(&a, &b));
+        assert_eq!(Rc::strong_count(&a), 1);
+        assert_eq!(Rc::strong_count(&b), 1);
+
+        let mut b = b;
+        Rc::get_mut(&mut b).expect("fresh copy is unique").push('!');
+        assert_eq!(*a, "shared");
+        assert_eq!(*b, "shared!");
+    }
+
+    #[test]
+    fn clone_from_same_allocation_is_noop() {
+        let a = Rc::new(5);
+        let mut b = a.clone();
+        b.clone_from(&a);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(Rc::strong_count(&a), 2);
+    }
+
+    #[test]
+    fn clone_from_drops_old_value() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let old_drops = Arc::new(AtomicUsize::new(0));
+        let source = Rc::new(DropSpy { drops: drops.clone(), _id: "source" });
+        let mut target = Rc::new(DropSpy { drops: old_drops.clone(), _id: "old" });
+        let old_alias = target.clone();
+
+        target.clone_from(&source);
+        assert!(Rc::ptr_eq(&target, &source));
+        assert_eq!(Rc::strong_count(&source), 2);
+        // still held by old_alias
+        assert_eq!(old_drops.load(Ordering::SeqCst), 0);
+
+        let mut other = old_alias;
+        other.clone_from(&source);
+        assert_eq!(old_drops.load(Ordering::SeqCst), 1);
+        assert_eq!(Rc::strong_count(&source), 3);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn weak_upgrades_while_strong_alive() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let a = Rc::new(DropSpy { drops: drops.clone(), _id: "weak" });
+        let w = Rc::downgrade(&a);
+
+        let b = w.upgrade().expect("strong Rc still alive");
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(Rc::strong_count(&a), 2);
+
+        drop(a);
+        drop(b);
+        // The value goes as soon as the last strong Rc does, even with a Weak around.
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn try_unwrap_with_outstanding_weak() {
+        let a = Rc::new(String::from("moved"));
+        let w = Rc::downgrade(&a);
+
+        let Ok(s) = Rc::try_unwrap(a) else {
+            panic!("expected unique access");
+        };
+        assert_eq!(s, "moved");
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn make_mut_with_outstanding_weak() {
+        let mut a = Rc::new(1);
+        let w = Rc::downgrade(&a);
+        *Rc::make_mut(&mut a) += 1;
+
+        assert_eq!(*a, 2);
+        // The Weak stays attached to the old allocation, which no Rc owns anymore.
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn new_cyclic_node_points_at_itself() {
+        struct Node {
+            me: Weak<Node>,
+            _spy: DropSpy,
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let node = Rc::new_cyclic(|me: &Weak<Node>| {
+            // Nothing to upgrade to until construction finishes.
+            assert!(me.upgrade().is_none());
+            Node {
+                me: me.clone(),
+                _spy: DropSpy { drops: drops.clone(), _id: "cyclic" },
+            }
+        });
+
+        let me = node.me.upgrade().expect("node is alive");
+        assert!(Rc::ptr_eq(&node, &me));
+        assert_eq!(Rc::strong_count(&node), 2);
+
+        drop(me);
+        drop(node);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn new_cyclic_panic_frees_allocation() {
+        let result = std::panic::catch_unwind(|| {
+            Rc::<i32>::new_cyclic(|_| panic!("constructor failed"));
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn weak_escaping_panicked_new_cyclic() {
+        let escaped = std::cell::RefCell::new(None);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            Rc::<Box<i32>>::new_cyclic(|w| {
+                *escaped.borrow_mut() = Some(w.clone());
+                panic!("constructor failed")
+            });
+        }));
+        assert!(result.is_err());
+        let w = escaped.into_inner().expect("data_fn stashed a Weak");
+        assert!(w.upgrade().is_none());
+        // Freeing the allocation must not look at the value that was never written.
+        drop(w);
+    }
+
+    #[test]
+    fn weak_outlives_dropped_value() {
+        let a = Rc::new(Box::new(5));
+        let w = Rc::downgrade(&a);
+        drop(a);
+        assert!(w.upgrade().is_none());
+        // The Box inside is gone by now; freeing must not look at it.
+        drop(w);
+
+        let s: Rc<[String]> = Rc::from(vec![String::from("a"), String::from("b")]);
+        let w = Rc::downgrade(&s);
+        drop(s);
+        drop(w);
+    }
+
+    #[test]
+    fn raw_round_trip_drops_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let a = Rc::new(DropSpy { drops: drops.clone(), _id: "raw" });
+        let b = a.clone();
+        let expected = Rc::as_ptr(&a);
+
+        let raw = Rc::into_raw(a);
+        assert_eq!(raw, expected);
+        // SAFETY: raw is live and points at the DropSpy.
+        assert_eq!(unsafe { (*raw)._id }, "raw");
+        assert_eq!(Rc::strong_count(&b), 2);
+
+        // SAFETY: raw came from into_raw and is only reconstituted once.
+        let a = unsafe { Rc::from_raw(raw) };
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(a._id, "raw");
+
+        drop(a);
+        drop(b);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn raw_round_trip_for_overaligned_and_unsized_values() {
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let a = Rc::new(Aligned(7));
+        // SAFETY: the pointer came from into_raw and is only reconstituted once.
+        let a = unsafe { Rc::from_raw(Rc::into_raw(a)) };
+        assert_eq!(a.0, 7);
+
+        let s: Rc<[u16]> = Rc::from(&[1, 2, 3][..]);
+        // SAFETY: as above.
+        let s = unsafe { Rc::from_raw(Rc::into_raw(s)) };
+        assert_eq!(&*s, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn increment_checked_refuses_to_overflow() {
+        let count = Cell::new(usize::MAX - 1);
+        assert_eq!(increment_checked(&count), Ok(()));
+        assert_eq!(count.get(), usize::MAX);
+
+        assert_eq!(increment_checked(&count), Err(RefcountOverflow));
+        assert_eq!(count.get(), usize::MAX);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // miri can't spawn processes
+    fn increment_aborts_on_overflow() {
+        const CHILD: &str = "POINTERS_RC_OVERFLOW_CHILD";
+        const MARKER: &str = "overflowing the refcount";
+        if std::env::var_os(CHILD).is_some() {
+            eprintln!("{MARKER}");
+            increment(&Cell::new(usize::MAX));
+            // Only reached if increment failed to abort.
+            std::process::exit(0);
+        }
+
+        // Re-run just this test in a child process, which should abort.
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "rc::tests::increment_aborts_on_overflow", "--nocapture"])
+            .env(CHILD, "1")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(MARKER), "child didn't run the test: {stderr}");
+        // A panic would also fail the child (with exit code 101), so check it really
+        // aborted.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            const SIGABRT: i32 = 6;
+            assert_eq!(
+                output.status.signal(),
+                Some(SIGABRT),
+                "child exited with {}",
+                output.status
+            );
+        }
+        #[cfg(not(unix))]
+        assert!(!output.status.success(), "child exited with {}", output.status);
+    }
+
+    #[test]
+    fn as_ref_reaches_through_rc() {
+        fn byte_len(data: impl AsRef<[u8]>) -> usize {
+            data.as_ref().len()
+        }
+
+        fn shout(s: impl AsRef<str>) -> String {
+            s.as_ref().to_uppercase()
+        }
+
+        let bytes: Rc<Vec<u8>> = Rc::new(vec![1, 2, 3]);
+        assert_eq!(byte_len(bytes.clone()), 3);
+        assert_eq!(byte_len(bytes), 3);
+
+        assert_eq!(shout(Rc::new(String::from("hi"))), "HI");
+    }
+
+    #[test]
+    fn borrow_allows_map_lookup_by_value() {
+        use std::collections::HashMap;
+
+        let mut map: HashMap<Rc<String>, u32> = HashMap::new();
+        map.insert(Rc::new(String::from("one")), 1);
+        map.insert(Rc::new(String::from("two")), 2);
+
+        let key = String::from("two");
+        assert_eq!(map.get(&key), Some(&2));
+        assert_eq!(map.get(&String::from("three")), None);
+    }
+
+    #[test]
+    fn new_uninit_then_assume_init() {
+        let mut buf = Rc::<[u8; 1024]>::new_uninit();
+        let slot = Rc::get_mut(&mut buf).expect("fresh Rc is unique");
+        let bytes = slot.as_mut_ptr().cast::<u8>();
+        for i in 0..1024 {
+            // SAFETY: bytes points at 1024 writable bytes inside the allocation.
+            unsafe { bytes.add(i).write(i as u8) };
+        }
+
+        // SAFETY: every byte was written above.
+        let buf = unsafe { Rc::assume_init(buf) };
+        assert_eq!(Rc::strong_count(&buf), 1);
+        assert!(buf.iter().enumerate().all(|(i, &b)| b == i as u8));
+    }
+
+    #[test]
+    fn pin_derefs_to_value() {
+        let pinned = Rc::pin(String::from("pinned"));
+        assert_eq!(*pinned, "pinned");
+        assert_eq!(pinned.len(), 6);
+    }
+
+    #[test]
+    fn pinned_address_is_stable_across_clones() {
+        let a = Rc::pin((42u64, std::marker::PhantomPinned));
+        let b = a.clone();
+        let pa: *const _ = &*a;
+        let pb: *const _ = &*b;
+        assert_eq!(pa, pb);
+
+        drop(a);
+        let pc: *const _ = &*b.clone();
+        assert_eq!(pb, pc);
+    }
+
+    #[test]
+    fn from_value() {
+        let r: Rc<i32> = 5.into();
+        assert_eq!(*r, 5);
+    }
+
+    #[test]
+    fn from_box_moves_value_and_drops_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let b = Box::new(DropSpy { drops: drops.clone(), _id: "boxed" });
+
+        let r: Rc<DropSpy> = Rc::from(b);
+        assert_eq!(r._id, "boxed");
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        drop(r);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn from_string() {
+        let r: Rc<str> = Rc::from(String::from("hello"));
+        let r2 = r.clone();
+        assert_eq!(&*r2, "hello");
+        assert_eq!(r.len(), 5);
+    }
+
+    #[test]
+    fn from_vec_moves_elements_and_drops_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let v = vec![
+            DropSpy { drops: drops.clone(), _id: "a" },
+            DropSpy { drops: drops.clone(), _id: "b" },
+        ];
+
+        let r: Rc<[DropSpy]> = Rc::from(v);
+        assert_eq!(r[1]._id, "b");
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        drop(r);
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn collect_from_exact_size_iterator() {
+        let r: Rc<[i32]> = (0..10).collect();
+        assert_eq!(&*r, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let spies: Rc<[DropSpy]> = (0..3)
+            .map(|_| DropSpy { drops: drops.clone(), _id: "exact" })
+            .collect();
+        assert_eq!(spies.len(), 3);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(spies);
+        assert_eq!(drops.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn collect_from_unknown_size_iterator() {
+        let r: Rc<[i32]> = (0..10).filter(|i| i % 3 == 0).collect();
+        assert_eq!(&*r, &[0, 3, 6, 9]);
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let spies: Rc<[DropSpy]> = (0..6)
+            .filter(|i| i % 2 == 0)
+            .map(|_| DropSpy { drops: drops.clone(), _id: "filtered" })
+            .collect();
+        assert_eq!(spies.len(), 3);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(spies);
+        assert_eq!(drops.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "more elements than promised")]
+    fn collect_from_lying_iterator_panics() {
+        struct Liar(u32);
+
+        impl Iterator for Liar {
+            type Item = u32;
+            fn next(&mut self) -> Option<u32> {
+                self.0 += 1;
+                (self.0 <= 3).then_some(self.0)
+            }
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (1, Some(1))
+            }
+        }
+
+        let _: Rc<[u32]> = Liar(0).collect();
+    }
+
+    #[test]
+    fn downcast_recovers_concrete_type() {
+        use std::any::Any;
+
+        // Stands in for the Rc<i32> -> Rc<dyn Any> coercion, which needs the nightly feature.
+        fn into_any<T: Any>(rc: Rc<T>) -> Rc<dyn Any> {
+            let rc = std::mem::ManuallyDrop::new(rc);
+            Rc {
+                inner: rc.inner,
+                _marker: std::marker::PhantomData,
+            }
+        }
+
+        let any = into_any(Rc::new(5i32));
+        let other = any.clone();
+
+        let Err(any) = any.downcast::<String>() else {
+            panic!("an i32 is not a String");
+        };
+        assert_eq!(any.downcast_ref::<i32>(), Some(&5));
+        assert_eq!(Rc::strong_count(&any), 2);
+
+        let Ok(five) = any.downcast::<i32>() else {
+            panic!("expected an i32");
+        };
+        assert_eq!(*five, 5);
+        assert_eq!(Rc::strong_count(&five), 2);
+        assert!(std::ptr::addr_eq(Rc::as_ptr(&five), Rc::as_ptr(&other)));
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn coerce_and_downcast() {
+        use std::any::Any;
+
+        let any: Rc<dyn Any> = Rc::new(5i32);
+        let Err(any) = any.downcast::<String>() else {
+            panic!("an i32 is not a String");
+        };
+        let Ok(five) = any.downcast::<i32>() else {
+            panic!("expected an i32");
+        };
+        assert_eq!(*five, 5);
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn coerce_to_dyn_display() {
+        use std::fmt;
+
+        struct Spy(DropSpy);
+
+        impl fmt::Display for Spy {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0._id)
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let spy = Rc::new(Spy(DropSpy { drops: drops.clone(), _id: "spy" }));
+        let shown: Vec<Rc<dyn fmt::Display>> = vec![
+            Rc::new(1),
+            Rc::new(String::from("two")),
+            Rc::new('3'),
+            spy.clone(),
+        ];
+
+        let formatted: Vec<String> = shown.iter().map(|d| d.to_string()).collect();
+        assert_eq!(formatted, ["1", "two", "3", "spy"]);
+
+        drop(spy);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(shown);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
 }
@@ -1,5 +1,7 @@
 use crate::cell::Cell;
 use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
 
 #[derive(Clone, Copy)]
 enum RefState {
@@ -24,91 +26,280 @@ impl<T> RefCell<T> {
         }
     }
 
-    pub fn borrow(&self) -> Option<Ref<'_, T>> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.try_borrow().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.try_borrow_mut().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
         match self.state.get() {
-            RefState::Unshared => {
-                self.state.set(RefState::Shared(1));
-                Some(Ref { refcell: self })
-            }
-            RefState::Shared(n) => {
-                self.state.set(RefState::Shared(n + 1));
-                Some(Ref { refcell: self })
-            }
-            RefState::Exclusive => None,
+            RefState::Unshared => self.state.set(RefState::Shared(1)),
+            RefState::Shared(n) => self.state.set(RefState::Shared(n + 1)),
+            RefState::Exclusive => return Err(BorrowError { _private: () }),
         }
+        Ok(Ref {
+            // SAFETY: UnsafeCell::get never returns null.
+            value: unsafe { NonNull::new_unchecked(self.value.get()) },
+            borrow: BorrowRef { state: &self.state },
+            _marker: PhantomData,
+        })
     }
 
-    pub fn borrow_mut(&self) -> Option<RefMut<'_, T>> {
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
         if let RefState::Unshared = self.state.get() {
             self.state.set(RefState::Exclusive);
             // SAFETY: no other references have been given out since state would be
             // Shared or Exclusive.
-            Some(RefMut { refcell: self })
+            Ok(RefMut {
+                // SAFETY: UnsafeCell::get never returns null.
+                value: unsafe { NonNull::new_unchecked(self.value.get()) },
+                borrow: BorrowRefMut { state: &self.state },
+                _marker: PhantomData,
+            })
         } else {
-            None
+            Err(BorrowMutError { _private: () })
+        }
+    }
+}
+
+/// Returned by [`RefCell::try_borrow`] when the value is already mutably borrowed.
+#[derive(Debug)]
+pub struct BorrowError {
+    _private: (),
+}
+
+impl std::fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("already mutably borrowed")
+    }
+}
+
+impl std::error::Error for BorrowError {}
+
+/// Returned by [`RefCell::try_borrow_mut`] when the value is already borrowed.
+#[derive(Debug)]
+pub struct BorrowMutError {
+    _private: (),
+}
+
+impl std::fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("already borrowed")
+    }
+}
+
+impl std::error::Error for BorrowMutError {}
+
+// Owns one shared borrow of a RefCell's state, and gives it back on drop. Keeping this
+// apart from the pointer to the value is what lets Ref::map change what the Ref points at.
+struct BorrowRef<'refcell> {
+    state: &'refcell Cell<RefState>,
+}
+
+impl Drop for BorrowRef<'_> {
+    fn drop(&mut self) {
+        match self.state.get() {
+            RefState::Exclusive | RefState::Unshared => unreachable!(),
+            RefState::Shared(1) => {
+                self.state.set(RefState::Unshared);
+            }
+            RefState::Shared(n) => {
+                self.state.set(RefState::Shared(n - 1));
+            }
         }
     }
 }
 
-pub struct Ref<'refcell, T> {
-    refcell: &'refcell RefCell<T>,
+pub struct Ref<'refcell, T: ?Sized> {
+    value: NonNull<T>,
+    borrow: BorrowRef<'refcell>,
+    _marker: PhantomData<&'refcell T>,
 }
 
-impl<T> std::ops::Deref for Ref<'_, T> {
+impl<'refcell, T: ?Sized> Ref<'refcell, T> {
+    /// Narrows the guard down to a part of the borrowed value. The new guard holds on to
+    /// the original borrow until it is dropped.
+    pub fn map<U: ?Sized, F>(orig: Ref<'refcell, T>, f: F) -> Ref<'refcell, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        Ref {
+            value: NonNull::from(f(&*orig)),
+            borrow: orig.borrow,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> std::ops::Deref for Ref<'_, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         // SAFETY
         // a Ref is only created if no exclusive references have been given out.
         // once it is given out, state is set to Shared, so no exclusive references are given out.
         // so dereferencing into a shared reference is fine.
-        unsafe { &*self.refcell.value.get() }
+        unsafe { self.value.as_ref() }
     }
 }
 
-impl<T> Drop for Ref<'_, T> {
+struct BorrowRefMut<'refcell> {
+    state: &'refcell Cell<RefState>,
+}
+
+impl Drop for BorrowRefMut<'_> {
     fn drop(&mut self) {
-        match self.refcell.state.get() {
-            RefState::Exclusive | RefState::Unshared => unreachable!(),
-            RefState::Shared(1) => {
-                self.refcell.state.set(RefState::Unshared);
-            }
-            RefState::Shared(n) => {
-                self.refcell.state.set(RefState::Shared(n - 1));
+        match self.state.get() {
+            RefState::Shared(_) | RefState::Unshared => unreachable!(),
+            RefState::Exclusive => {
+                self.state.set(RefState::Unshared);
             }
         }
     }
 }
 
-pub struct RefMut<'refcell, T> {
-    refcell: &'refcell RefCell<T>,
+pub struct RefMut<'refcell, T: ?Sized> {
+    value: NonNull<T>,
+    borrow: BorrowRefMut<'refcell>,
+    _marker: PhantomData<&'refcell mut T>,
 }
 
-impl<T> std::ops::Deref for RefMut<'_, T> {
+impl<'refcell, T: ?Sized> RefMut<'refcell, T> {
+    /// Narrows the guard down to a part of the borrowed value. The new guard holds on to
+    /// the original borrow until it is dropped.
+    pub fn map<U: ?Sized, F>(mut orig: RefMut<'refcell, T>, f: F) -> RefMut<'refcell, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        RefMut {
+            value: NonNull::from(f(&mut *orig)),
+            borrow: orig.borrow,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> std::ops::Deref for RefMut<'_, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         // SAFETY
         // see safety for DerefMut
-        unsafe { &*self.refcell.value.get() }
+        unsafe { self.value.as_ref() }
     }
 }
 
-impl<T> std::ops::DerefMut for RefMut<'_, T> {
+impl<T: ?Sized> std::ops::DerefMut for RefMut<'_, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         // SAFETY
         // a RefMut is only created if no other references have been given out.
         // once it is given out, state is set to Exclusive, so no future references are given out.
         // so we have an exclusive lease on the inner value, so mutably dereferencing is fine.
-        unsafe { &mut *self.refcell.value.get() }
+        unsafe { self.value.as_mut() }
     }
 }
 
-impl<T> Drop for RefMut<'_, T> {
-    fn drop(&mut self) {
-        match self.refcell.state.get() {
-            RefState::Shared(_) | RefState::Unshared => unreachable!(),
-            RefState::Exclusive => {
-                self.refcell.state.set(RefState::Unshared);
+#[cfg(test)]
+mod tests {
+    use super::{Ref, RefCell, RefMut, RefState};
+
+    #[test]
+    fn many_shared_borrows() {
+        let c = RefCell::new(5);
+        let a = c.borrow();
+        let b = c.borrow();
+        assert_eq!(*a + *b, 10);
+        assert!(matches!(c.state.get(), RefState::Shared(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn borrow_mut_while_shared_panics() {
+        let c = RefCell::new(5);
+        let _a = c.borrow();
+        let _b = c.borrow_mut();
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn borrow_while_exclusive_panics() {
+        let c = RefCell::new(5);
+        let _a = c.borrow_mut();
+        let _b = c.borrow();
+    }
+
+    #[test]
+    fn guards_restore_state_on_drop() {
+        let c = RefCell::new(String::from("a"));
+        {
+            let _a = c.borrow();
+            {
+                let _b = c.borrow();
+                assert!(matches!(c.state.get(), RefState::Shared(2)));
             }
+            assert!(matches!(c.state.get(), RefState::Shared(1)));
         }
+        assert!(matches!(c.state.get(), RefState::Unshared));
+
+        c.borrow_mut().push('b');
+        assert!(matches!(c.state.get(), RefState::Unshared));
+        assert_eq!(*c.borrow(), "ab");
+    }
+
+    #[test]
+    fn try_borrow_mut_fails_while_shared() {
+        let c = RefCell::new(5);
+        let a = c.borrow();
+
+        let err = c.try_borrow_mut().err().expect("shared borrow is active");
+        assert_eq!(err.to_string(), "already borrowed");
+        // The failed attempt left the count alone.
+        assert!(matches!(c.state.get(), RefState::Shared(1)));
+
+        drop(a);
+        *c.try_borrow_mut().expect("no conflicting guard left") += 1;
+        assert_eq!(*c.borrow(), 6);
+    }
+
+    #[test]
+    fn try_borrow_fails_while_exclusive() {
+        let c = RefCell::new(5);
+        let a = c.borrow_mut();
+
+        let err: Box<dyn std::error::Error> = c.try_borrow().err().unwrap().into();
+        assert_eq!(err.to_string(), "already mutably borrowed");
+        assert!(matches!(c.state.get(), RefState::Exclusive));
+
+        drop(a);
+        assert_eq!(*c.try_borrow().expect("no conflicting guard left"), 5);
+    }
+
+    #[test]
+    fn ref_map_keeps_borrow_alive() {
+        let c = RefCell::new((1, String::from("field")));
+        let field: Ref<'_, String> = Ref::map(c.borrow(), |pair| &pair.1);
+        assert_eq!(*field, "field");
+
+        assert!(c.try_borrow_mut().is_err());
+        assert!(matches!(c.state.get(), RefState::Shared(1)));
+
+        let s: Ref<'_, str> = Ref::map(field, |s| &s[1..]);
+        assert_eq!(&*s, "ield");
+        assert!(c.try_borrow_mut().is_err());
+
+        drop(s);
+        assert!(c.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn ref_mut_map_projects_mutably() {
+        let c = RefCell::new((1, String::from("field")));
+        let mut field = RefMut::map(c.borrow_mut(), |pair| &mut pair.1);
+        field.push('s');
+
+        assert!(c.try_borrow().is_err());
+        drop(field);
+        assert_eq!(c.borrow().1, "fields");
     }
 }
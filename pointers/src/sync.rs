@@ -0,0 +1,288 @@
+use std::alloc::{self, Layout};
+use std::marker::PhantomData;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+
+// Refuse to go anywhere near wrapping the count; see SyncRc::clone.
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+// As with Rc, the strong references together hold one implicit weak reference, so the
+// allocation outlives the value for as long as any SyncWeak still points at it.
+struct SyncRcInner<T> {
+    refcount: AtomicUsize,
+    weak: AtomicUsize,
+    value: T,
+}
+
+// Only the count fields may be borrowed through these: another thread can be dropping the
+// value once the strong count hits zero.
+fn refcount<'a, T>(inner: NonNull<SyncRcInner<T>>) -> &'a AtomicUsize {
+    // SAFETY: the counts stay valid until the allocation is freed, which waits on the weak
+    // count, and every caller holds a strong or weak reference.
+    unsafe { &*ptr::addr_of!((*inner.as_ptr()).refcount) }
+}
+
+fn weak<'a, T>(inner: NonNull<SyncRcInner<T>>) -> &'a AtomicUsize {
+    // SAFETY: as for refcount.
+    unsafe { &*ptr::addr_of!((*inner.as_ptr()).weak) }
+}
+
+/// An atomically reference counted [`Rc`](crate::rc::Rc), safe to share across threads.
+pub struct SyncRc<T> {
+    inner: NonNull<SyncRcInner<T>>,
+    _marker: PhantomData<SyncRcInner<T>>,
+}
+
+// SAFETY: a SyncRc hands out &T on any thread that has a clone (so T: Sync), and whichever
+// thread drops the last clone drops the T (so T: Send). The count itself is atomic.
+unsafe impl<T: Send + Sync> Send for SyncRc<T> {}
+unsafe impl<T: Send + Sync> Sync for SyncRc<T> {}
+
+impl<T> SyncRc<T> {
+    pub fn new(v: T) -> Self {
+        let boxed = Box::new(SyncRcInner {
+            refcount: AtomicUsize::new(1),
+            weak: AtomicUsize::new(1),
+            value: v,
+        });
+        SyncRc {
+            inner: NonNull::from(Box::leak(boxed)),
+            _marker: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &SyncRcInner<T> {
+        // SAFETY: inner points to a valid SyncRcInner while any SyncRc to it is alive.
+        unsafe { self.inner.as_ref() }
+    }
+
+    pub fn downgrade(this: &Self) -> SyncWeak<T> {
+        if weak(this.inner).fetch_add(1, Ordering::Relaxed) > MAX_REFCOUNT {
+            std::process::abort();
+        }
+        SyncWeak {
+            inner: this.inner,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        refcount(this.inner).load(Ordering::Acquire)
+    }
+
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        a.inner == b.inner
+    }
+}
+
+impl<T> std::ops::Deref for SyncRc<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.inner().value
+    }
+}
+
+impl<T> Clone for SyncRc<T> {
+    fn clone(&self) -> Self {
+        // Relaxed is enough: we already hold a reference, so the value can't go away under
+        // us, and making a new reference doesn't need to synchronize with anything.
+        let old = refcount(self.inner).fetch_add(1, Ordering::Relaxed);
+        // Other threads may race past this check before we abort, but not the ~isize::MAX
+        // of them it would take to actually wrap the count.
+        if old > MAX_REFCOUNT {
+            std::process::abort();
+        }
+        SyncRc {
+            inner: self.inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for SyncRc<T> {
+    fn drop(&mut self) {
+        // Release makes our uses of the value happen-before the decrement, and so before
+        // the final drop, whichever thread ends up doing it.
+        if refcount(self.inner).fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        // Pairs with the Release decrements of every other SyncRc, so that all their uses
+        // of the value happen-before we drop it.
+        atomic::fence(Ordering::Acquire);
+        // SAFETY: we were the last SyncRc, and upgrade never brings a zero count back, so
+        // no-one else can reach the value.
+        unsafe { ptr::drop_in_place(ptr::addr_of_mut!((*self.inner.as_ptr()).value)) };
+        // Give up the implicit weak reference held by the strong ones.
+        drop(SyncWeak {
+            inner: self.inner,
+            _marker: PhantomData,
+        });
+    }
+}
+
+/// A non-owning reference to a [`SyncRc`]'s value, safe to share across threads.
+pub struct SyncWeak<T> {
+    inner: NonNull<SyncRcInner<T>>,
+    _marker: PhantomData<SyncRcInner<T>>,
+}
+
+// SAFETY: a SyncWeak can be upgraded into a SyncRc on whatever thread holds it.
+unsafe impl<T: Send + Sync> Send for SyncWeak<T> {}
+unsafe impl<T: Send + Sync> Sync for SyncWeak<T> {}
+
+impl<T> SyncWeak<T> {
+    pub fn upgrade(&self) -> Option<SyncRc<T>> {
+        let count = refcount(self.inner);
+        let mut n = count.load(Ordering::Relaxed);
+        loop {
+            // Once the count reaches zero the value is gone (or going), and stays gone.
+            if n == 0 {
+                return None;
+            }
+            if n > MAX_REFCOUNT {
+                std::process::abort();
+            }
+            // A plain fetch_add could bump a count that another thread just took to zero;
+            // only move it up from the non-zero value we actually saw. Acquire pairs with
+            // the Release in SyncRc::drop, as for any other new owner of the value.
+            match count.compare_exchange_weak(n, n + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => {
+                    return Some(SyncRc {
+                        inner: self.inner,
+                        _marker: PhantomData,
+                    });
+                }
+                Err(current) => n = current,
+            }
+        }
+    }
+}
+
+impl<T> Clone for SyncWeak<T> {
+    fn clone(&self) -> Self {
+        if weak(self.inner).fetch_add(1, Ordering::Relaxed) > MAX_REFCOUNT {
+            std::process::abort();
+        }
+        SyncWeak {
+            inner: self.inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for SyncWeak<T> {
+    fn drop(&mut self) {
+        if weak(self.inner).fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        atomic::fence(Ordering::Acquire);
+        // SAFETY: the value was already dropped along with the implicit weak reference, and
+        // we were the last weak, so nothing points at the allocation anymore.
+        unsafe { alloc::dealloc(self.inner.as_ptr().cast(), Layout::new::<SyncRcInner<T>>()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SyncRc, SyncWeak};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A value that bumps a shared counter when dropped.
+    struct DropSpy<'a> {
+        drops: &'a AtomicUsize,
+    }
+
+    impl Drop for DropSpy<'_> {
+        fn drop(&mut self) {
+            self.drops.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SyncRc<String>>();
+        assert_send_sync::<SyncWeak<String>>();
+    }
+
+    #[test]
+    fn clones_share_value() {
+        let a = SyncRc::new(5);
+        let b = a.clone();
+        assert!(SyncRc::ptr_eq(&a, &b));
+        assert_eq!(*b, 5);
+        assert_eq!(SyncRc::strong_count(&a), 2);
+        drop(b);
+        assert_eq!(SyncRc::strong_count(&a), 1);
+    }
+
+    #[test]
+    fn many_threads_drop_inner_exactly_once() {
+        let (threads, rounds) = if cfg!(miri) { (4, 10) } else { (16, 1000) };
+        let drops = AtomicUsize::new(0);
+        let shared = SyncRc::new(DropSpy { drops: &drops });
+
+        std::thread::scope(|s| {
+            for _ in 0..threads {
+                let mine = shared.clone();
+                s.spawn(move || {
+                    let mut held = Vec::new();
+                    for _ in 0..rounds {
+                        held.push(mine.clone());
+                        if held.len() > 8 {
+                            held.clear();
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        assert_eq!(SyncRc::strong_count(&shared), 1);
+        drop(shared);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn weak_upgrade_and_expire() {
+        let drops = AtomicUsize::new(0);
+        let strong = SyncRc::new(DropSpy { drops: &drops });
+        let weak = SyncRc::downgrade(&strong);
+        let upgraded = weak.upgrade().expect("value is still alive");
+        assert!(SyncRc::ptr_eq(&strong, &upgraded));
+        assert_eq!(SyncRc::strong_count(&strong), 2);
+        drop(upgraded);
+        drop(strong);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        assert!(weak.upgrade().is_none());
+        assert!(weak.clone().upgrade().is_none());
+    }
+
+    #[test]
+    fn upgrade_races_final_drop() {
+        let rounds = if cfg!(miri) { 5 } else { 200 };
+        for _ in 0..rounds {
+            let drops = AtomicUsize::new(0);
+            let strong = SyncRc::new(DropSpy { drops: &drops });
+            let weak = SyncRc::downgrade(&strong);
+
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    while let Some(rc) = weak.upgrade() {
+                        // Reading through the upgraded pointer lets miri catch any
+                        // use-after-free.
+                        assert_eq!(rc.drops.load(Ordering::SeqCst), 0);
+                    }
+                    // Once dead, always dead.
+                    for _ in 0..10 {
+                        assert!(weak.upgrade().is_none());
+                    }
+                });
+                s.spawn(move || drop(strong));
+            });
+
+            assert_eq!(drops.load(Ordering::SeqCst), 1);
+            assert!(weak.upgrade().is_none());
+        }
+    }
+}
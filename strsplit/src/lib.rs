@@ -5,6 +5,17 @@ pub struct StrSplit<'haystack, D> {
 	pub remainder: Option<&'haystack str>,
 	pub delimiter: D,
 	empty_leading_pending: bool,
+	ended_with_delimiter: bool,
+	peeked: Option<Peeked<'haystack>>,
+}
+
+/// A piece computed by `peek`, along with the state to resume from once it's taken.
+#[derive(Debug, PartialEq)]
+struct Peeked<'haystack> {
+	piece: Option<&'haystack str>,
+	remainder: Option<&'haystack str>,
+	empty_leading_pending: bool,
+	ended_with_delimiter: bool,
 }
 
 impl<'haystack, D> StrSplit<'haystack, D> {
@@ -13,23 +24,64 @@ impl<'haystack, D> StrSplit<'haystack, D> {
 			remainder: Some(haystack),
 			delimiter,
 			empty_leading_pending: true,
+			ended_with_delimiter: false,
+			peeked: None,
 		}
 	}
-}
 
-pub trait Delimiter {
-	fn find_next(&self, s: &str) -> Option<(usize, usize)>;
+	/// Splits the part of `haystack` from byte `start` on, exactly as if that suffix
+	/// were the whole haystack.
+	///
+	/// Panics if `start` is out of bounds or not on a char boundary, like slicing.
+	pub fn from_offset(haystack: &'haystack str, delimiter: D, start: usize) -> Self {
+		Self::new(&haystack[start..], delimiter)
+	}
+
+	/// The part of the haystack not yet yielded, or `None` once splitting is done.
+	///
+	/// A piece that has only been peeked at still counts as remaining.
+	pub fn remainder(&self) -> Option<&'haystack str> {
+		self.remainder
+	}
+
+	/// Whether the haystack ended in a delimiter, i.e. `"a,b,"` rather than `"a,b"`.
+	///
+	/// Only settled once iteration is done. Zero-width matches (such as an empty `&str`
+	/// delimiter) never count, and neither does an empty haystack: both report `false`.
+	pub fn ended_with_delimiter(&self) -> bool {
+		self.ended_with_delimiter
+	}
 }
 
-impl<'haystack, D> Iterator for StrSplit<'haystack, D>
+impl<'haystack, D> StrSplit<'haystack, D>
 where
 	D: Delimiter,
 {
-	type Item = &'haystack str;
+	/// Returns the piece the next call to `next` will yield, without consuming it.
+	pub fn peek(&mut self) -> Option<&'haystack str> {
+		if self.peeked.is_none() {
+			let remainder = self.remainder;
+			let empty_leading_pending = self.empty_leading_pending;
+			let ended_with_delimiter = self.ended_with_delimiter;
+			let piece = self.step();
+			self.peeked = Some(Peeked {
+				piece,
+				remainder: self.remainder,
+				empty_leading_pending: self.empty_leading_pending,
+				ended_with_delimiter: self.ended_with_delimiter,
+			});
+			// put the state back, so the peeked piece still shows up in `remainder()`
+			self.remainder = remainder;
+			self.empty_leading_pending = empty_leading_pending;
+			self.ended_with_delimiter = ended_with_delimiter;
+		}
+		self.peeked.as_ref().and_then(|peeked| peeked.piece)
+	}
 
-	fn next(&mut self) -> Option<Self::Item> {
+	fn step(&mut self) -> Option<&'haystack str> {
 		let s = self.remainder.take()?;
 		if let Some((start, end)) = self.delimiter.find_next(s) {
+			debug_assert!(start <= end && end <= s.len(), "delimiter range out of bounds");
 			if start == end {
 				if self.empty_leading_pending {
 					self.empty_leading_pending = false;
@@ -52,6 +104,7 @@ where
 			let head = &s[..start];
 			let tail = &s[end..];
 			self.empty_leading_pending = true; // reset for next boundary
+			self.ended_with_delimiter = end == s.len();
 			self.remainder = Some(tail);
 			Some(head)
 		} else {
@@ -59,9 +112,331 @@ where
 			Some(s)
 		}
 	}
+
+	/// Splits `haystack` into `out` without allocating, returning how many slots were
+	/// filled; slots past that are left alone.
+	///
+	/// Like [`str::splitn`], if there are more fields than slots, the last slot gets
+	/// everything that's left, unsplit.
+	pub fn parse_fields(
+		haystack: &'haystack str,
+		delimiter: D,
+		out: &mut [&'haystack str],
+	) -> usize {
+		let mut split = StrSplit::new(haystack, delimiter);
+		let last = out.len().saturating_sub(1);
+		for (i, slot) in out.iter_mut().enumerate() {
+			let field = if i == last {
+				split.remainder.take()
+			} else {
+				split.next()
+			};
+			match field {
+				Some(field) => *slot = field,
+				None => return i,
+			}
+		}
+		out.len()
+	}
+
+	/// Total length in bytes of all remaining pieces, i.e. everything but the delimiters.
+	pub fn sum_len(self) -> usize {
+		self.fold(0, |len, piece| len + piece.len())
+	}
+
+	/// Trims whitespace off every piece. Unlike filtering out empties, this keeps
+	/// every slot: an all-whitespace piece comes out as `""`.
+	pub fn trimmed(self) -> impl Iterator<Item = &'haystack str> {
+		self.map(str::trim)
+	}
+
+	/// Yields the pieces in lexicographic order.
+	///
+	/// This buffers every remaining piece before yielding the first one, so it
+	/// is a convenience terminal rather than a streaming adaptor.
+	pub fn sorted(self) -> impl Iterator<Item = &'haystack str> {
+		let mut pieces: Vec<_> = self.collect();
+		pieces.sort_unstable();
+		pieces.into_iter()
+	}
+
+	/// Like [`StrSplit::sorted`], but yields each distinct piece only once.
+	pub fn sorted_dedup(self) -> impl Iterator<Item = &'haystack str> {
+		let mut pieces: Vec<_> = self.collect();
+		pieces.sort_unstable();
+		pieces.dedup();
+		pieces.into_iter()
+	}
+
+	/// Splits `haystack` on `delimiter`, pairing every piece with the nesting depth of
+	/// `open`/`close` at the point where the piece starts.
+	///
+	/// When `open == close` (e.g. quotes) each occurrence toggles between depth 0 and 1.
+	pub fn with_depth(
+		haystack: &'haystack str,
+		delimiter: D,
+		open: char,
+		close: char,
+	) -> impl Iterator<Item = (u32, &'haystack str)> {
+		let mut scanned = 0;
+		let mut depth = 0u32;
+		StrSplit::new(haystack, delimiter).map(move |piece| {
+			let start = offset_in(haystack, piece);
+			for ch in haystack[scanned..start].chars() {
+				if ch == open && ch == close {
+					depth = if depth == 0 { 1 } else { 0 };
+				} else if ch == open {
+					depth += 1;
+				} else if ch == close {
+					depth = depth.saturating_sub(1);
+				}
+			}
+			scanned = start;
+			(depth, piece)
+		})
+	}
+
+	/// Splits `haystack` into alternating fields and delimiters, so that concatenating
+	/// the text of every token reproduces `haystack` exactly.
+	pub fn lossless_tokens(haystack: &'haystack str, delimiter: D) -> LosslessTokens<'haystack, D> {
+		LosslessTokens {
+			haystack,
+			pieces: StrSplit::new(haystack, delimiter),
+			consumed: 0,
+			started: false,
+			pending: None,
+		}
+	}
+
+	/// Like [`StrSplit::lossless_tokens`], but as [`Piece`]s: the text between matches,
+	/// interleaved with the delimiter text that actually matched.
+	pub fn split_with_delimiters(
+		haystack: &'haystack str,
+		delimiter: D,
+	) -> impl Iterator<Item = Piece<'haystack>> {
+		StrSplit::lossless_tokens(haystack, delimiter).map(|token| match token {
+			Token::Field(text) => Piece::Text(text),
+			Token::Delim(delim) => Piece::Delim(delim),
+		})
+	}
+}
+
+impl<'haystack> StrSplit<'haystack, LineEnding> {
+	/// Splits `haystack` into lines like [`str::lines`]: on `\n` or `\r\n`, with no
+	/// empty line after a final line ending.
+	pub fn lines(haystack: &'haystack str) -> Lines<'haystack> {
+		Lines {
+			inner: StrSplit::new(haystack, LineEnding),
+		}
+	}
+}
+
+pub struct Lines<'haystack> {
+	inner: StrSplit<'haystack, LineEnding>,
+}
+
+impl<'haystack> Iterator for Lines<'haystack> {
+	type Item = &'haystack str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let line = self.inner.next()?;
+		// only the last piece can be empty with nothing after it: that's the
+		// "line" after a final line ending (or an empty haystack)
+		if line.is_empty() && self.inner.remainder().is_none() {
+			return None;
+		}
+		Some(line)
+	}
+}
+
+/// A [`StrSplit`] that knows how many pieces it has left, so it can be an
+/// [`ExactSizeIterator`].
+///
+/// The count comes from splitting the haystack once up front, so construction
+/// costs a full pass over it.
+#[derive(Debug)]
+pub struct StrSplitExact<'haystack, D> {
+	inner: StrSplit<'haystack, D>,
+	remaining: usize,
+}
+
+impl<'haystack, D> StrSplitExact<'haystack, D>
+where
+	D: Delimiter + Clone,
+{
+	pub fn new(haystack: &'haystack str, delimiter: D) -> Self {
+		let remaining = StrSplit::new(haystack, delimiter.clone()).count();
+		Self {
+			inner: StrSplit::new(haystack, delimiter),
+			remaining,
+		}
+	}
+}
+
+impl<'haystack, D> Iterator for StrSplitExact<'haystack, D>
+where
+	D: Delimiter,
+{
+	type Item = &'haystack str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let piece = self.inner.next()?;
+		self.remaining -= 1;
+		Some(piece)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<D> ExactSizeIterator for StrSplitExact<'_, D> where D: Delimiter {}
+
+/// A delimiter configured once and then used to split any number of haystacks.
+#[derive(Debug, Clone)]
+pub struct Splitter<D> {
+	delimiter: D,
+}
+
+impl<D> Splitter<D> {
+	pub fn new(delimiter: D) -> Self {
+		Self { delimiter }
+	}
+
+	pub fn split<'haystack>(&self, haystack: &'haystack str) -> StrSplit<'haystack, ByRef<'_, D>> {
+		StrSplit::new(haystack, ByRef(&self.delimiter))
+	}
+}
+
+/// A borrowed delimiter, as used by [`Splitter::split`].
+///
+/// This can't just be `impl Delimiter for &D`: that would overlap with the
+/// `Fn(char) -> bool` impl.
+#[derive(Debug)]
+pub struct ByRef<'delimiter, D>(&'delimiter D);
+
+impl<D> Delimiter for ByRef<'_, D>
+where
+	D: Delimiter,
+{
+	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		self.0.find_next(s)
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token<'haystack> {
+	Field(&'haystack str),
+	Delim(&'haystack str),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Piece<'haystack> {
+	Text(&'haystack str),
+	Delim(&'haystack str),
+}
+
+impl<'haystack> Token<'haystack> {
+	pub fn text(&self) -> &'haystack str {
+		match *self {
+			Token::Field(s) | Token::Delim(s) => s,
+		}
+	}
 }
 
+pub struct LosslessTokens<'haystack, D> {
+	haystack: &'haystack str,
+	pieces: StrSplit<'haystack, D>,
+	consumed: usize,
+	started: bool,
+	pending: Option<&'haystack str>,
+}
+
+impl<'haystack, D> Iterator for LosslessTokens<'haystack, D>
+where
+	D: Delimiter,
+{
+	type Item = Token<'haystack>;
 
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(field) = self.pending.take() {
+			self.consumed = offset_in(self.haystack, field) + field.len();
+			return Some(Token::Field(field));
+		}
+
+		let field = self.pieces.next()?;
+		let start = offset_in(self.haystack, field);
+		if !self.started {
+			// the first field has no delimiter in front of it
+			self.started = true;
+			self.consumed = field.len();
+			return Some(Token::Field(field));
+		}
+
+		// whatever lies between the previous field and this one is the delimiter
+		self.pending = Some(field);
+		Some(Token::Delim(&self.haystack[self.consumed..start]))
+	}
+}
+
+/// Byte offset of `piece` within `haystack`, which it must be a sub-slice of.
+fn offset_in(haystack: &str, piece: &str) -> usize {
+	piece.as_ptr() as usize - haystack.as_ptr() as usize
+}
+
+/// Finds delimiters in a haystack for [`StrSplit`].
+///
+/// Matching is non-overlapping and greedy from the left: `find_next` reports the
+/// leftmost match, and the search for the next one starts where that match ended.
+/// So `"aaaa"` split on `"aa"` is `["", "", ""]`, and `"aaa"` is `["", "a"]`.
+pub trait Delimiter {
+	/// Returns the byte range `(start, end)` of the first match in `s`, with
+	/// `start <= end <= s.len()`, both on char boundaries. An empty range is a
+	/// zero-width match.
+	fn find_next(&self, s: &str) -> Option<(usize, usize)>;
+}
+
+impl<'haystack, D> Iterator for StrSplit<'haystack, D>
+where
+	D: Delimiter,
+{
+	type Item = &'haystack str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(peeked) = self.peeked.take() {
+			self.remainder = peeked.remainder;
+			self.empty_leading_pending = peeked.empty_leading_pending;
+			self.ended_with_delimiter = peeked.ended_with_delimiter;
+			return peeked.piece;
+		}
+		self.step()
+	}
+
+	fn fold<B, F>(mut self, init: B, mut f: F) -> B
+	where
+		F: FnMut(B, Self::Item) -> B,
+	{
+		let mut acc = init;
+		if let Some(peeked) = self.peeked.take() {
+			match peeked.piece {
+				Some(piece) => acc = f(acc, piece),
+				None => return acc,
+			}
+			self.remainder = peeked.remainder;
+			self.empty_leading_pending = peeked.empty_leading_pending;
+			self.ended_with_delimiter = peeked.ended_with_delimiter;
+		}
+		// past the peek, nothing else can be cached, so step straight through
+		while let Some(piece) = self.step() {
+			acc = f(acc, piece);
+		}
+		acc
+	}
+}
+
+
+/// Matches the leftmost occurrence of the string; see [`Delimiter`] for how
+/// repeated occurrences like `"aa"` in `"aaa"` are handled.
 impl Delimiter for &str {
 	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
 		if self.is_empty() {
@@ -74,6 +449,12 @@ impl Delimiter for &str {
 
 impl Delimiter for char {
 	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		// An ASCII byte never shows up inside a multi-byte UTF-8 sequence, so a
+		// plain byte search finds exactly the same matches as str::find.
+		#[cfg(feature = "memchr")]
+		if self.is_ascii() {
+			return memchr::memchr(*self as u8, s.as_bytes()).map(|start| (start, start + 1));
+		}
 		s.find(*self).map(|start| (start, start + self.len_utf8()))
 	}
 }
@@ -90,6 +471,23 @@ impl Delimiter for &[char] {
 	}
 }
 
+/// Matches whichever of the strings occurs first; if several start at the same
+/// place, the one listed first wins.
+impl Delimiter for &[&str] {
+	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		// One pass over s, trying every delimiter at each position, rather than a
+		// full search per delimiter: that would rescan the rest of the haystack for
+		// a delimiter that doesn't occur on every single piece. Matches can only
+		// start on a char boundary, since no delimiter starts with a continuation byte.
+		let bytes = s.as_bytes();
+		(0..=bytes.len()).find_map(|start| {
+			self.iter()
+				.find(|d| bytes[start..].starts_with(d.as_bytes()))
+				.map(|d| (start, start + d.len()))
+		})
+	}
+}
+
 impl<F> Delimiter for F
 where
 	F: Fn(char) -> bool,
@@ -101,9 +499,42 @@ where
 	}
 }
 
+/// Matches `\n` along with a `\r` right before it. A `\r` on its own is not a line ending.
+#[derive(Debug, Clone, Copy)]
+pub struct LineEnding;
+
+impl Delimiter for LineEnding {
+	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		let newline = '\n'.find_next(s)?;
+		match newline {
+			(start, end) if s[..start].ends_with('\r') => Some((start - 1, end)),
+			found => Some(found),
+		}
+	}
+}
+
+/// Matches any single char that the wrapped predicate or char set does *not* match.
+#[derive(Debug, Clone, Copy)]
+pub struct Not<P>(pub P);
+
+impl<F> Delimiter for Not<F>
+where
+	F: Fn(char) -> bool,
+{
+	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		(|ch| !(self.0)(ch)).find_next(s)
+	}
+}
+
+impl Delimiter for Not<&[char]> {
+	fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+		Not(|ch| self.0.contains(&ch)).find_next(s)
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use crate::StrSplit;
+	use crate::{Delimiter, Not, Piece, Splitter, StrSplit, StrSplitExact, Token};
 
 	pub fn until_char(s: &str, c: char) -> &'_ str {
 		let delim = format!("{}", c);
@@ -214,4 +645,472 @@ mod tests {
 		let splits: Vec<_> = StrSplit::new(&haystack, "").collect();
 		assert_eq!(splits, &["", "r", "u", "s", "t", ""]);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn sorted() {
+		let haystack = "pear,apple,fig,apple";
+		let splits: Vec<_> = StrSplit::new(haystack, ',').sorted().collect();
+		assert_eq!(splits, ["apple", "apple", "fig", "pear"]);
+	}
+
+	#[test]
+	fn sorted_dedup() {
+		let haystack = "pear,apple,fig,apple,,pear";
+		let splits: Vec<_> = StrSplit::new(haystack, ',').sorted_dedup().collect();
+		assert_eq!(splits, ["", "apple", "fig", "pear"]);
+	}
+
+	#[test]
+	fn with_depth() {
+		let haystack = "a(b,c),d";
+		let splits: Vec<_> = StrSplit::with_depth(haystack, ',', '(', ')').collect();
+		assert_eq!(splits, [(0, "a(b"), (1, "c)"), (0, "d")]);
+	}
+
+	#[test]
+	fn with_depth_nested() {
+		let haystack = "f(g(x,y),z),w";
+		let splits: Vec<_> = StrSplit::with_depth(haystack, ',', '(', ')').collect();
+		assert_eq!(splits, [(0, "f(g(x"), (2, "y)"), (1, "z)"), (0, "w")]);
+	}
+
+	#[test]
+	fn with_depth_quotes() {
+		let haystack = "a,\"b,c\",d";
+		let splits: Vec<_> = StrSplit::with_depth(haystack, ',', '"', '"').collect();
+		assert_eq!(splits, [(0, "a"), (0, "\"b"), (1, "c\""), (0, "d")]);
+	}
+
+	#[test]
+	fn lossless_tokens() {
+		let tokens: Vec<_> = StrSplit::lossless_tokens("a, b", ", ").collect();
+		assert_eq!(tokens, [Token::Field("a"), Token::Delim(", "), Token::Field("b")]);
+	}
+
+	#[test]
+	fn lossless_tokens_round_trip() {
+		for haystack in ["", ",", "a,b", ",a,b", "a,b,", ",,a,,b,,", "||||a||b|c"] {
+			for delim in [",", "|", ",,"] {
+				let tokens: String = StrSplit::lossless_tokens(haystack, delim)
+					.map(|t| t.text())
+					.collect();
+				assert_eq!(tokens, haystack);
+			}
+		}
+
+		let tokens: String = StrSplit::lossless_tokens("rust", "").map(|t| t.text()).collect();
+		assert_eq!(tokens, "rust");
+	}
+
+	#[test]
+	fn lossless_tokens_empty_delimiter() {
+		let tokens: Vec<_> = StrSplit::lossless_tokens("ab", "").collect();
+		assert_eq!(
+			tokens,
+			[
+				Token::Field(""),
+				Token::Delim(""),
+				Token::Field("a"),
+				Token::Delim(""),
+				Token::Field("b"),
+				Token::Delim(""),
+				Token::Field(""),
+			]
+		);
+	}
+
+	#[test]
+	fn lossless_tokens_alternate() {
+		let tokens: Vec<_> = StrSplit::lossless_tokens(",a,,", ',').collect();
+		assert_eq!(
+			tokens,
+			[
+				Token::Field(""),
+				Token::Delim(","),
+				Token::Field("a"),
+				Token::Delim(","),
+				Token::Field(""),
+				Token::Delim(","),
+				Token::Field(""),
+			]
+		);
+	}
+
+	#[test]
+	fn ascii_char_matches_generic_path() {
+		let haystacks = [
+			"",
+			",",
+			"a,b,,c,",
+			"héllo,wörld,日本,語",
+			",€,,🦀,a",
+			"no delimiter here: ünïcödé",
+		];
+		for haystack in haystacks {
+			for delim in [',', 'a', 'é', '🦀'] {
+				let fast: Vec<_> = StrSplit::new(haystack, delim).collect();
+				let generic: Vec<_> = StrSplit::new(haystack, |c| c == delim).collect();
+				assert_eq!(fast, generic, "{haystack:?} split on {delim:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn splitter_reuse() {
+		let comma = Splitter::new(", ");
+		let a: Vec<_> = comma.split("a, b, c").collect();
+		let b: Vec<_> = comma.split("").collect();
+		let c: Vec<_> = comma.split("x, , y, ").collect();
+		assert_eq!(a, ["a", "b", "c"]);
+		assert_eq!(b, [""]);
+		assert_eq!(c, ["x", "", "y", ""]);
+
+		let any: &[char] = &[';', '|'];
+		let fields = Splitter::new(any);
+		for line in ["1;2|3", "4|5;6"] {
+			assert_eq!(fields.split(line).count(), 3);
+		}
+		assert_eq!(fields.split("7;8").collect::<Vec<_>>(), ["7", "8"]);
+
+		// the delimiter is only borrowed, so it needn't be Clone
+		struct OneOf(Vec<char>);
+		impl Delimiter for OneOf {
+			fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+				self.0.as_slice().find_next(s)
+			}
+		}
+		let owned = Splitter::new(OneOf(vec!['+', '-']));
+		assert_eq!(owned.split("1+2-3").collect::<Vec<_>>(), ["1", "2", "3"]);
+		assert_eq!(owned.split("4").collect::<Vec<_>>(), ["4"]);
+	}
+
+	#[test]
+	fn not_predicate() {
+		let words: Vec<_> = StrSplit::new("ab!!cd??ef", Not(char::is_alphanumeric))
+			.filter(|piece| !piece.is_empty())
+			.collect();
+		assert_eq!(words, ["ab", "cd", "ef"]);
+
+		// all delimiters: only empty pieces, one more than there are delimiters
+		let pieces: Vec<_> = StrSplit::new("!?", Not(char::is_alphanumeric)).collect();
+		assert_eq!(pieces, ["", "", ""]);
+
+		// no delimiters: the whole haystack
+		let pieces: Vec<_> = StrSplit::new("abc123", Not(char::is_alphanumeric)).collect();
+		assert_eq!(pieces, ["abc123"]);
+	}
+
+	#[test]
+	fn not_char_set() {
+		let digits: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+		let numbers: Vec<_> = StrSplit::new("12ab3é45", Not(digits))
+			.filter(|piece| !piece.is_empty())
+			.collect();
+		assert_eq!(numbers, ["12", "3", "45"]);
+		assert_eq!(StrSplit::new("xyz", Not(digits)).count(), 4);
+		assert_eq!(StrSplit::new("2024", Not(digits)).collect::<Vec<_>>(), ["2024"]);
+	}
+
+	#[test]
+	#[ignore = "timing benchmark; run with --ignored --nocapture"]
+	fn bench_ascii_char() {
+		use std::time::Instant;
+
+		// what `impl Delimiter for char` does without the memchr feature
+		struct StrFind(char);
+		impl Delimiter for StrFind {
+			fn find_next(&self, s: &str) -> Option<(usize, usize)> {
+				s.find(self.0).map(|start| (start, start + self.0.len_utf8()))
+			}
+		}
+
+		let dense = "field,".repeat(2_000_000);
+		let sparse = format!("{},", "x".repeat(199)).repeat(50_000);
+		for (name, haystack) in [("dense", &dense), ("sparse", &sparse)] {
+			let start = Instant::now();
+			let char_count = StrSplit::new(haystack, ',').count();
+			let char_time = start.elapsed();
+			let start = Instant::now();
+			let find_count = StrSplit::new(haystack, StrFind(',')).count();
+			let find_time = start.elapsed();
+
+			assert_eq!(char_count, find_count);
+			println!("{name}: char delimiter {char_time:?}, str::find {find_time:?}");
+		}
+	}
+
+	#[test]
+	fn remainder_accessor() {
+		let mut split = StrSplit::new("a,b", ",");
+		assert_eq!(split.remainder(), Some("a,b"));
+		split.next();
+		assert_eq!(split.remainder(), Some("b"));
+		split.next();
+		assert_eq!(split.remainder(), None);
+	}
+
+	#[test]
+	fn peek_then_next() {
+		let mut split = StrSplit::new("a,b,", ",");
+		assert_eq!(split.peek(), Some("a"));
+		assert_eq!(split.peek(), Some("a"));
+		assert_eq!(split.remainder(), Some("a,b,"));
+		assert_eq!(split.next(), Some("a"));
+		assert_eq!(split.next(), Some("b"));
+		assert_eq!(split.peek(), Some(""));
+		assert_eq!(split.next(), Some(""));
+		assert_eq!(split.peek(), None);
+		assert_eq!(split.next(), None);
+	}
+
+	#[test]
+	fn peek_with_empty_delimiter() {
+		let expected: Vec<_> = StrSplit::new("ab", "").collect();
+		let mut split = StrSplit::new("ab", "");
+		let mut peeked = Vec::new();
+		while let Some(piece) = split.peek() {
+			assert_eq!(split.next(), Some(piece));
+			peeked.push(piece);
+		}
+		assert_eq!(peeked, expected);
+		assert_eq!(split.next(), None);
+	}
+
+	#[test]
+	fn trimmed() {
+		let fields: Vec<_> = StrSplit::new("a , b ,c", ',').trimmed().collect();
+		assert_eq!(fields, ["a", "b", "c"]);
+		let fields: Vec<_> = StrSplit::new("  ,x", ',').trimmed().collect();
+		assert_eq!(fields, ["", "x"]);
+		let fields: Vec<_> = StrSplit::new(" , ,", ',').trimmed().collect();
+		assert_eq!(fields, ["", "", ""]);
+	}
+
+	#[test]
+	fn ended_with_delimiter() {
+		fn ends(haystack: &str, delimiter: &str) -> bool {
+			let mut split = StrSplit::new(haystack, delimiter);
+			split.by_ref().for_each(drop);
+			split.ended_with_delimiter()
+		}
+
+		assert!(!ends("a,b", ","));
+		assert!(ends("a,b,", ","));
+		assert!(ends(",", ","));
+		assert!(ends("a,,", ","));
+		assert!(!ends("", ","));
+		assert!(!ends("ab", ""));
+		assert!(!ends("", ""));
+	}
+
+	#[test]
+	fn ended_with_delimiter_survives_peek() {
+		let mut split = StrSplit::new("a,b,", ",");
+		assert_eq!(split.next(), Some("a"));
+		assert_eq!(split.peek(), Some("b"));
+		// peeking consumed the trailing delimiter only on the side
+		assert!(!split.ended_with_delimiter());
+		assert_eq!(split.next(), Some("b"));
+		assert!(split.ended_with_delimiter());
+		assert_eq!(split.next(), Some(""));
+		assert_eq!(split.peek(), None);
+		assert!(split.ended_with_delimiter());
+	}
+
+	#[test]
+	fn split_with_delimiters() {
+		use Piece::{Delim, Text};
+
+		let arrows: &[&str] = &["->", "=>"];
+		let pieces: Vec<_> = StrSplit::split_with_delimiters("a->b=>c", arrows).collect();
+		assert_eq!(
+			pieces,
+			[Text("a"), Delim("->"), Text("b"), Delim("=>"), Text("c")]
+		);
+
+		let pieces: Vec<_> = StrSplit::split_with_delimiters("x,y", ',').collect();
+		assert_eq!(pieces, [Text("x"), Delim(","), Text("y")]);
+
+		// adjacent delimiters leave an empty text between them
+		let pieces: Vec<_> = StrSplit::split_with_delimiters("a=>->b", arrows).collect();
+		assert_eq!(
+			pieces,
+			[Text("a"), Delim("=>"), Text(""), Delim("->"), Text("b")]
+		);
+	}
+
+	#[test]
+	fn str_set_long_input() {
+		let haystack = "ab->".repeat(100_000);
+		for set in [&["->", "=>"][..], &["=>", "->"][..]] {
+			let mut split = StrSplit::new(&haystack, set);
+			assert!(split.by_ref().take(100_000).all(|piece| piece == "ab"));
+			assert_eq!(split.collect::<Vec<_>>(), [""]);
+		}
+	}
+
+	#[test]
+	fn str_set_prefers_earliest_then_first_listed() {
+		let set: &[&str] = &["ab", "a", "b"];
+		let pieces: Vec<_> = StrSplit::new("xaby", set).collect();
+		assert_eq!(pieces, ["x", "y"]);
+		let set: &[&str] = &["a", "ab"];
+		let pieces: Vec<_> = StrSplit::new("xaby", set).collect();
+		assert_eq!(pieces, ["x", "by"]);
+		let set: &[&str] = &["z", "y"];
+		let pieces: Vec<_> = StrSplit::new("xaby", set).collect();
+		assert_eq!(pieces, ["xab", ""]);
+	}
+
+	#[test]
+	fn overlapping_matches_go_left_to_right() {
+		let pieces: Vec<_> = StrSplit::new("aaaa", "aa").collect();
+		assert_eq!(pieces, ["", "", ""]);
+		let pieces: Vec<_> = StrSplit::new("aaa", "aa").collect();
+		assert_eq!(pieces, ["", "a"]);
+		let pieces: Vec<_> = StrSplit::new("abababa", "aba").collect();
+		assert_eq!(pieces, ["", "b", ""]);
+	}
+
+	#[test]
+	fn exact_len_counts_down() {
+		let mut split = StrSplitExact::new("a,b,c", ',');
+		for expected in ["a", "b", "c"] {
+			let len = split.len();
+			assert_eq!(split.next(), Some(expected));
+			assert_eq!(split.len(), len - 1);
+		}
+		assert_eq!(split.len(), 0);
+		assert_eq!(split.next(), None);
+
+		let mut split = StrSplitExact::new("a,b,", ",");
+		assert_eq!(split.len(), 3);
+		split.next();
+		split.next();
+		assert_eq!(split.len(), 1);
+		assert_eq!(split.next(), Some(""));
+		assert_eq!(split.len(), 0);
+		assert_eq!(split.next(), None);
+	}
+
+	#[test]
+	fn exact_len_agrees_with_count() {
+		for haystack in ["", ",", "a", "a,,b", ",a,", "日,本"] {
+			for delim in [",", ""] {
+				let expected = StrSplit::new(haystack, delim).count();
+				assert_eq!(StrSplitExact::new(haystack, delim).len(), expected);
+			}
+			let expected = StrSplit::new(haystack, ',').count();
+			assert_eq!(StrSplitExact::new(haystack, ',').len(), expected);
+		}
+	}
+
+	#[test]
+	fn lines() {
+		fn lines(haystack: &str) -> Vec<&str> {
+			StrSplit::lines(haystack).collect()
+		}
+
+		assert_eq!(lines("a\nb"), ["a", "b"]);
+		assert_eq!(lines("a\r\nb\r\n"), ["a", "b"]);
+		assert_eq!(lines("a\nb\r\nc"), ["a", "b", "c"]);
+		assert_eq!(lines("a\n\nb\n"), ["a", "", "b"]);
+		// a lone \r is part of the line
+		assert_eq!(lines("a\rb\r\n"), ["a\rb"]);
+		assert_eq!(lines("a\r"), ["a\r"]);
+		assert_eq!(lines("\n"), [""]);
+		assert!(lines("").is_empty());
+
+		for haystack in ["a\nb", "a\r\nb\r\n", "\r\n\r\nx", "x\n\n", "\r"] {
+			assert_eq!(lines(haystack), haystack.lines().collect::<Vec<_>>());
+		}
+	}
+
+	#[test]
+	fn parse_fields() {
+		let seps: &[char] = &['-', ' ', ':'];
+		let stamp = "2020-11-03 23:59:30";
+		let mut out = [""; 6];
+		assert_eq!(StrSplit::parse_fields(stamp, seps, &mut out), 6);
+		assert_eq!(out, ["2020", "11", "03", "23", "59", "30"]);
+
+		let mut out = [""; 3];
+		assert_eq!(StrSplit::parse_fields(stamp, seps, &mut out), 3);
+		assert_eq!(out, ["2020", "11", "03 23:59:30"]);
+
+		// fewer fields than slots: the rest stay as they were
+		let mut out = ["-"; 6];
+		assert_eq!(StrSplit::parse_fields("2020-11-03", seps, &mut out), 3);
+		assert_eq!(out, ["2020", "11", "03", "-", "-", "-"]);
+
+		let mut out = [""; 2];
+		assert_eq!(StrSplit::parse_fields("a,", ',', &mut out), 2);
+		assert_eq!(out, ["a", ""]);
+		assert_eq!(StrSplit::parse_fields("a,b", ',', &mut []), 0);
+	}
+
+	#[test]
+	fn from_offset_matches_suffix() {
+		for haystack in ["a,b,,c,", ",x", "日,本,"] {
+			let boundaries = haystack.char_indices().map(|(i, _)| i).chain([haystack.len()]);
+			for start in boundaries {
+				let resumed: Vec<_> = StrSplit::from_offset(haystack, ',', start).collect();
+				let fresh: Vec<_> = StrSplit::new(&haystack[start..], ',').collect();
+				assert_eq!(resumed, fresh, "{haystack:?} from {start}");
+			}
+		}
+
+		// the empty delimiter starts over with its leading empty piece
+		let pieces: Vec<_> = StrSplit::from_offset("abc", "", 1).collect();
+		assert_eq!(pieces, ["", "b", "c", ""]);
+	}
+
+	#[test]
+	#[should_panic(expected = "is not a char boundary")]
+	fn from_offset_inside_char_panics() {
+		StrSplit::from_offset("日本", ',', 1);
+	}
+
+	#[test]
+	fn fold_matches_next() {
+		fn check<D: Delimiter + Clone>(haystack: &str, delimiter: D) {
+			let mut expected = Vec::new();
+			for piece in StrSplit::new(haystack, delimiter.clone()) {
+				expected.push(piece);
+			}
+			let pieces = StrSplit::new(haystack, delimiter.clone());
+			let folded = pieces.fold(Vec::new(), |mut v, p| {
+				v.push(p);
+				v
+			});
+			assert_eq!(folded, expected, "{haystack:?}");
+
+			// folding after a peek starts with the peeked piece
+			let mut split = StrSplit::new(haystack, delimiter);
+			split.peek();
+			assert_eq!(split.fold(0, |n, _| n + 1), expected.len());
+		}
+
+		check("Mary had a little lamb", " ");
+		check("", "X");
+		check("lion::tiger::leopard", "::");
+		check("abc1def2ghi", char::is_numeric);
+		check("lionXtigerXleopard", char::is_uppercase);
+		check("2020-11-03 23:59", &['-', ' ', ':', '@'][..]);
+		check("abc1defXghi", |c| c == '1' || c == 'X');
+		check("||||a||b|c", '|');
+		check("(///)", '/');
+		check("010", "0");
+		check("    a  b c", ' ');
+		check("rust", "");
+		check("a,b,", ",");
+	}
+
+	#[test]
+	fn sum_len() {
+		assert_eq!(StrSplit::new("a b c", ' ').sum_len(), 3);
+		assert_eq!(StrSplit::new("lion::tiger", "::").sum_len(), 9);
+		assert_eq!(StrSplit::new("", ',').sum_len(), 0);
+		assert_eq!(StrSplit::new("rust", "").sum_len(), 4);
+	}
+}